@@ -1,12 +1,276 @@
-use clap::Parser;
+use clap::{Parser, Subcommand, ValueEnum};
+use oxd::dict::DictClient;
+use oxd::models::catalog::LabelKind;
+use oxd::{Language, LookupFilters, TranslationLanguage};
+use std::fmt;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 #[derive(Debug, Parser)]
-#[clap(author, version, about)]
+#[clap(author, version, about, args_conflicts_with_subcommands = true)]
 pub struct OxdArgs {
     /// The term to look up
-    pub word: String,
+    #[arg(required = true)]
+    pub word: Option<String>,
 
     /// Play pronunciation file from API
-    #[arg(short, long, default_value_t = false)]
+    #[arg(short, long, default_value_t = false, conflicts_with = "offline")]
     pub sound: bool,
+
+    /// Show up to N corpus sentences under each sense
+    #[arg(long, value_name = "N", default_value_t = 0)]
+    pub sentences: usize,
+
+    /// Only show these parts of speech, e.g. verb
+    #[arg(long, value_name = "POS", value_delimiter = ',')]
+    pub pos: Vec<String>,
+
+    /// Only show senses of these subject areas, e.g. computing
+    #[arg(long, value_name = "DOMAIN", value_delimiter = ',')]
+    pub domain: Vec<String>,
+
+    /// Only show senses of these registers, e.g. informal
+    #[arg(long, value_name = "REGISTER", value_delimiter = ',')]
+    pub register: Vec<String>,
+
+    /// Only show entries with these grammatical features, e.g. plural
+    #[arg(long, value_name = "FEATURE", value_delimiter = ',')]
+    pub feature: Vec<String>,
+
+    /// Only match headwords spelled exactly like the term
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+
+    /// Always ask the API, without reading or writing cached responses
+    #[arg(long, global = true, default_value_t = false)]
+    pub no_cache: bool,
+
+    /// Ask the API even if a response is cached, and cache the new one
+    #[arg(
+        long,
+        global = true,
+        default_value_t = false,
+        conflicts_with = "no_cache"
+    )]
+    pub refresh: bool,
+
+    /// Answer only from cached responses, without connecting to the API
+    #[arg(long, global = true, default_value_t = false, conflicts_with_all = ["no_cache", "refresh"])]
+    pub offline: bool,
+
+    /// Dictionary language: en-gb, en-us, es, fr, gu, hi, lv, ro, sw or ta
+    #[arg(short, long, global = true, default_value_t = Language::EnUs)]
+    pub lang: Language,
+
+    /// Where entries come from: od (the Oxford Dictionary API), wordnet, stardict:<PATH>,
+    /// wiktionary, optionally with a language code other than --lang, e.g. wiktionary:de,
+    /// or a DICT server, e.g. dict://dict.org/wn
+    #[arg(long, global = true, value_name = "SOURCE", default_value = "od")]
+    pub source: Source,
+
+    /// The WordNet dict/ directory; defaults to $WNSEARCHDIR, $WNHOME/dict or /usr/share/wordnet
+    #[arg(long, global = true, value_name = "DIR")]
+    pub wordnet_dir: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl OxdArgs {
+    /// The lookup filters given on the command line.
+    pub fn filters(&self) -> LookupFilters {
+        LookupFilters {
+            lexical_categories: self.pos.clone(),
+            domains: self.domain.clone(),
+            registers: self.register.clone(),
+            grammatical_features: self.feature.clone(),
+            fields: vec![],
+            strict_match: self.strict.then_some(true),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Source {
+    /// The Oxford Dictionary API
+    Od,
+    /// A local Princeton WordNet database, English only
+    Wordnet,
+    /// A StarDict dictionary: its .ifo file, the path of its files without extension,
+    /// or a directory holding one dictionary
+    Stardict(PathBuf),
+    /// Words imported from a Wiktionary extract, in the given language or that of --lang
+    Wiktionary(Option<String>),
+    /// A DICT server, as dict://HOST[:PORT][/DATABASE]
+    Dict(String),
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        match source {
+            "od" => Ok(Source::Od),
+            "wordnet" => Ok(Source::Wordnet),
+            "wiktionary" => Ok(Source::Wiktionary(None)),
+            _ => match source.split_once(':') {
+                Some(("stardict", path)) if !path.is_empty() => {
+                    Ok(Source::Stardict(PathBuf::from(path)))
+                }
+                Some(("wiktionary", code)) if !code.is_empty() => {
+                    Ok(Source::Wiktionary(Some(code.to_owned())))
+                }
+                Some(("dict", _)) => Ok(Source::Dict(source.to_owned())),
+                _ => Err(format!(
+                    "expected od, wordnet, stardict:<PATH>, wiktionary[:<LANG>] or dict://<HOST>, not \"{source}\""
+                )),
+            },
+        }
+    }
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Source::Od => write!(f, "od"),
+            Source::Wordnet => write!(f, "wordnet"),
+            Source::Stardict(path) => write!(f, "stardict:{}", path.display()),
+            Source::Wiktionary(None) => write!(f, "wiktionary"),
+            Source::Wiktionary(Some(code)) => write!(f, "wiktionary:{code}"),
+            Source::Dict(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Look up synonyms and antonyms
+    Syn {
+        /// The term to look up
+        word: String,
+    },
+    /// Translate a word with a bilingual dictionary
+    Translate {
+        /// Language of the word, e.g. en
+        #[arg(long, default_value_t = TranslationLanguage::ENGLISH)]
+        from: TranslationLanguage,
+
+        /// Language to translate into, e.g. es
+        #[arg(long)]
+        to: TranslationLanguage,
+
+        /// The term to translate
+        word: String,
+    },
+    /// List the values OD uses for labels, dictionaries, fields or filters
+    Catalog {
+        /// What to list
+        kind: CatalogKind,
+    },
+    /// Fetch the entries of a word list into the cache
+    Prefetch {
+        /// A file with one term per line
+        file: PathBuf,
+
+        /// Fetch every word again instead of resuming where the last run stopped
+        #[arg(long, default_value_t = false)]
+        restart: bool,
+    },
+    /// Answer DICT protocol (RFC 2229) clients such as dict or GoldenDict with oxd lookups
+    ServeDict {
+        /// The port to listen on
+        #[arg(long, default_value_t = DictClient::DEFAULT_PORT)]
+        port: u16,
+
+        /// The address to listen on; 0.0.0.0 answers other machines too
+        #[arg(long, value_name = "ADDRESS", default_value = "127.0.0.1")]
+        bind: String,
+    },
+    /// Import a Wiktionary extract from kaikki.org for --source wiktionary
+    ImportWiktionary {
+        /// The extract, in JSON lines, optionally gzipped
+        file: PathBuf,
+    },
+    /// Inspect, clean up or move cached responses
+    Cache {
+        #[command(subcommand)]
+        action: CacheAction,
+    },
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub enum CacheAction {
+    /// List cached responses with their age
+    List,
+    /// Show the cached responses for a word
+    Show {
+        /// The term to show
+        word: String,
+
+        /// Print the JSON as the API sent it
+        #[arg(long, default_value_t = false)]
+        json: bool,
+    },
+    /// Remove cached responses
+    Purge {
+        /// Only remove responses older than this many days
+        #[arg(long, value_name = "DAYS")]
+        older_than: Option<u64>,
+    },
+    /// Count cached responses per language and their total size
+    Stats,
+    /// Write every cached response to a single archive file
+    Export {
+        /// The archive to write
+        file: PathBuf,
+    },
+    /// Add the responses of an archive written by export
+    Import {
+        /// The archive to read
+        file: PathBuf,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum CatalogKind {
+    Domains,
+    Registers,
+    Regions,
+    LexicalCategories,
+    GrammaticalFeatures,
+    Languages,
+    Fields,
+    Filters,
+}
+
+impl CatalogKind {
+    /// The label list to fetch, or `None` for languages, fields and filters.
+    pub fn label_kind(self) -> Option<LabelKind> {
+        match self {
+            CatalogKind::Domains => Some(LabelKind::Domains),
+            CatalogKind::Registers => Some(LabelKind::Registers),
+            CatalogKind::Regions => Some(LabelKind::Regions),
+            CatalogKind::LexicalCategories => Some(LabelKind::LexicalCategories),
+            CatalogKind::GrammaticalFeatures => Some(LabelKind::GrammaticalFeatures),
+            CatalogKind::Languages | CatalogKind::Fields | CatalogKind::Filters => None,
+        }
+    }
+}
+
+#[test]
+fn test_args() {
+    use clap::CommandFactory;
+    OxdArgs::command().debug_assert();
+
+    let args = OxdArgs::parse_from(["oxd", "--source", "stardict:dic/metals", "rust"]);
+    assert_eq!(args.source, Source::Stardict(PathBuf::from("dic/metals")));
+    assert!(OxdArgs::try_parse_from(["oxd", "--source", "stardict:", "rust"]).is_err());
+    let args = OxdArgs::parse_from(["oxd", "--source", "wiktionary:de", "Rost"]);
+    assert_eq!(args.source, Source::Wiktionary(Some("de".to_owned())));
+    assert_eq!(args.source.to_string(), "wiktionary:de");
+    let args = OxdArgs::parse_from(["oxd", "serve-dict", "--source", "wordnet"]);
+    assert!(matches!(
+        args.command,
+        Some(Command::ServeDict { port: 2628, .. })
+    ));
 }
@@ -0,0 +1,312 @@
+/*! An async client for the OD API, enabled by the `async` feature */
+
+use crate::client::{ClientConfig, OxdClientBuilder, Request};
+use crate::error::OxdError;
+use crate::language::{Language, TranslationLanguage};
+use crate::lookup::{Lookup, LookupFilters, RootResolver};
+use crate::models::{
+    catalog::{EndpointOptions, LabelKind, Labels, Languages},
+    lemmas::Lemmatron,
+    search::SearchResults,
+    sentences::Sentences,
+    thesaurus::Thesaurus,
+    RetrieveEntry,
+};
+use futures_util::stream::{self, StreamExt};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// Entries fetched while looking up many words, keyed by headword.
+///
+/// Each slot is `None` until fetched, then holds the entry or `None` if it is missing.
+type EntryMemo = Mutex<HashMap<String, Arc<tokio::sync::Mutex<Option<Option<RetrieveEntry>>>>>>;
+
+/// An async client for the OD API, created with [AsyncOxdClient::builder].
+///
+/// It offers the same lookups as [OxdClient](crate::OxdClient),
+/// returning futures instead of blocking the current thread.
+#[derive(Debug, Clone)]
+pub struct AsyncOxdClient {
+    http: reqwest::Client,
+    config: ClientConfig,
+}
+
+impl AsyncOxdClient {
+    pub(crate) fn new(http: reqwest::Client, config: ClientConfig) -> Self {
+        AsyncOxdClient { http, config }
+    }
+
+    /// Starts configuring a client, finished with [OxdClientBuilder::build_async].
+    pub fn builder() -> OxdClientBuilder {
+        OxdClientBuilder::default()
+    }
+
+    /// The dictionary language this client queries.
+    pub fn source_lang(&self) -> Language {
+        self.config.source_lang
+    }
+
+    /// Queries the API and returns a single [RetrieveEntry](crate::models::RetrieveEntry).
+    pub async fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        self.entry_with(word, &LookupFilters::default()).await
+    }
+
+    /// Like [entry](Self::entry), keeping only what matches `filters`.
+    ///
+    /// Whatever the endpoint does not filter itself is [pruned](LookupFilters::prune),
+    /// and an entry left empty is reported as [NotFound](OxdError::NotFound).
+    pub async fn entry_with(
+        &self,
+        word: &str,
+        filters: &LookupFilters,
+    ) -> Result<RetrieveEntry, OxdError> {
+        let entry = self.get(self.config.entry_request(word, filters)).await?;
+        filters.narrow(entry, word)
+    }
+
+    /// Looks up a word together with its headwords and their root forms.
+    ///
+    /// The lemmas endpoint resolves inflected forms first,
+    /// so "running" finds "run" and "mice" finds "mouse".
+    /// Root forms are followed up to [root_depth](OxdClientBuilder::root_depth) steps,
+    /// fetching each once. Headwords and root forms missing from the API, or from the cache
+    /// when [offline](OxdClientBuilder::offline), are skipped.
+    pub async fn entries(&self, word: &str) -> Result<Lookup, OxdError> {
+        self.entries_with(word, &LookupFilters::default()).await
+    }
+
+    /// Like [entries](Self::entries), keeping only what matches `filters`
+    /// in the entries of headwords and root forms alike.
+    pub async fn entries_with(
+        &self,
+        word: &str,
+        filters: &LookupFilters,
+    ) -> Result<Lookup, OxdError> {
+        self.lookup(word, filters, None).await
+    }
+
+    /// Looks up many words at once like [entries](Self::entries),
+    /// with up to `concurrency` lookups in flight
+    /// on this client's connection pool.
+    ///
+    /// Root forms shared by several words, such as "rust" for "rusty" and "rusted",
+    /// are fetched once. Results come back in the order of `words`,
+    /// each with its own error.
+    pub async fn get_many<S: AsRef<str>>(
+        &self,
+        words: &[S],
+        concurrency: usize,
+    ) -> Vec<Result<Lookup, OxdError>> {
+        self.get_many_with(words, &LookupFilters::default(), concurrency)
+            .await
+    }
+
+    /// Like [get_many](Self::get_many), keeping only what matches `filters`.
+    pub async fn get_many_with<S: AsRef<str>>(
+        &self,
+        words: &[S],
+        filters: &LookupFilters,
+        concurrency: usize,
+    ) -> Vec<Result<Lookup, OxdError>> {
+        let memo = EntryMemo::default();
+        stream::iter(words)
+            .map(|word| self.lookup(word.as_ref(), filters, Some(&memo)))
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+
+    /// Queries the lemmas endpoint, which links inflected forms to their headwords.
+    pub async fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        self.get(self.config.lemmas_request(word)).await
+    }
+
+    /// Searches headwords similar to `query`, e.g. to suggest words
+    /// when [entries](Self::entries) finds nothing.
+    pub async fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        self.get(self.config.search_request(query, limit)).await
+    }
+
+    /// Searches headwords of the thesaurus similar to `query`.
+    pub async fn search_thesaurus(
+        &self,
+        query: &str,
+        limit: usize,
+    ) -> Result<SearchResults, OxdError> {
+        self.get(self.config.search_thesaurus_request(query, limit))
+            .await
+    }
+
+    /// Queries corpus sentences, linked to senses by [Sense::id](crate::models::Sense::id).
+    ///
+    /// Use [attach_sentences](crate::models::sentences::attach_sentences) to show them under
+    /// the senses of an entry.
+    pub async fn sentences(&self, word: &str) -> Result<Sentences, OxdError> {
+        self.get(self.config.sentences_request(word)).await
+    }
+
+    /// Queries a bilingual dictionary and returns an entry whose
+    /// [senses](crate::models::Sense) carry [translations](crate::models::Translation).
+    pub async fn translations(
+        &self,
+        source: TranslationLanguage,
+        target: TranslationLanguage,
+        word: &str,
+    ) -> Result<RetrieveEntry, OxdError> {
+        self.get(self.config.translations_request(source, target, word))
+            .await
+    }
+
+    /// Queries the thesaurus and returns synonyms and antonyms grouped by sense.
+    pub async fn thesaurus(&self, word: &str) -> Result<Thesaurus, OxdError> {
+        self.get(self.config.thesaurus_request(word)).await
+    }
+
+    /// Lists the valid values of a label in the source language, e.g. every domain.
+    pub async fn labels(&self, kind: LabelKind) -> Result<Labels, OxdError> {
+        self.get(self.config.labels_request(kind)).await
+    }
+
+    /// Lists the monolingual and bilingual dictionaries the API offers.
+    pub async fn languages(&self) -> Result<Languages, OxdError> {
+        self.get(self.config.catalog_request(&["languages"])).await
+    }
+
+    /// Lists the fields each endpoint can return.
+    pub async fn fields(&self) -> Result<EndpointOptions, OxdError> {
+        self.get(self.config.catalog_request(&["fields"])).await
+    }
+
+    /// Lists the filters each endpoint accepts.
+    pub async fn filters(&self) -> Result<EndpointOptions, OxdError> {
+        self.get(self.config.catalog_request(&["filters"])).await
+    }
+
+    /// Downloads an audio file such as [Pronunciation::audio_file](crate::models::Pronunciation::audio_file).
+    ///
+    /// Credentials are not sent along, as audio files are hosted elsewhere.
+    pub async fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        self.config.check_online(url)?;
+        let res = self.http.get(url).send().await?;
+        if let Some(err) = OxdError::from_status(res.status(), res.headers(), url) {
+            return Err(err);
+        }
+        Ok(res.bytes().await?.to_vec())
+    }
+
+    /// Resolves a word to its headwords and their root forms,
+    /// sharing entries through `memo` when looking up many words.
+    async fn lookup(
+        &self,
+        word: &str,
+        filters: &LookupFilters,
+        memo: Option<&EntryMemo>,
+    ) -> Result<Lookup, OxdError> {
+        let lemmas = self.lemmas(word).await;
+        let mut resolver = RootResolver::start(word, lemmas, self.config.root_depth)?;
+        while let Some(headword) = resolver.next() {
+            let entry = self.headword_entry(headword, filters, memo).await;
+            resolver.found(entry)?;
+        }
+        resolver
+            .finish()
+            .map_err(|_| self.config.nothing_found(word))
+    }
+
+    /// Fetches the entry of a headword or root form.
+    ///
+    /// With a `memo`, a headword is fetched once however many lookups ask for it;
+    /// the others wait for its entry. Failures other than a missing entry are not remembered.
+    async fn headword_entry(
+        &self,
+        headword: &str,
+        filters: &LookupFilters,
+        memo: Option<&EntryMemo>,
+    ) -> Result<RetrieveEntry, OxdError> {
+        let Some(memo) = memo else {
+            return self.entry_with(headword, filters).await;
+        };
+        let slot = Arc::clone(memo.lock().unwrap().entry(headword.to_owned()).or_default());
+        let mut slot = slot.lock().await;
+        if slot.is_none() {
+            *slot = Some(match self.entry_with(headword, filters).await {
+                Ok(entry) => Some(entry),
+                Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => None,
+                Err(err) => return Err(err),
+            });
+        }
+        slot.clone()
+            .flatten()
+            .ok_or_else(|| OxdError::NotFound(headword.to_owned()))
+    }
+
+    /// Answers a request from what the client remembers, the
+    /// [ResponseCache](crate::cache::ResponseCache) or else the API, and decodes the response.
+    async fn get<T: DeserializeOwned + Send + 'static>(
+        &self,
+        request: Request,
+    ) -> Result<T, OxdError> {
+        if let Some(answer) = self.config.remembered(&request) {
+            return answer;
+        }
+        let key = request.key.clone();
+        if let Some(cached) = self.off_workers(move |config| config.cached(&key)).await {
+            return Ok(cached);
+        }
+        let body = self.fetch(&request).await?;
+        self.off_workers(move |config| config.answer(&request, body))
+            .await
+    }
+
+    /// Sends a GET request with credentials and returns the response body,
+    /// retrying as the [RetryPolicy](crate::retry::RetryPolicy) allows.
+    async fn fetch(&self, request: &Request) -> Result<String, OxdError> {
+        self.config.check_online(&request.word)?;
+        let mut attempt = 1;
+        loop {
+            match self.send(request).await {
+                Err(err) => match self
+                    .config
+                    .retry
+                    .retry_delay(request.url.as_str(), attempt, &err)
+                {
+                    Some(delay) => tokio::time::sleep(delay).await,
+                    None => return Err(err),
+                },
+                body => return body,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a single request, once the rate limiter lets it through.
+    async fn send(&self, request: &Request) -> Result<String, OxdError> {
+        if let Some(rate_limiter) = self.config.rate_limiter.clone() {
+            let wait = self.off_workers(move |_| rate_limiter.acquire()).await?;
+            tokio::time::sleep(wait).await;
+        }
+        let res = self
+            .http
+            .get(request.url.clone())
+            .headers(self.config.credentials.clone())
+            .send()
+            .await?;
+        if let Some(err) = OxdError::from_status(res.status(), res.headers(), &request.word) {
+            return Err(err);
+        }
+        Ok(res.text().await?)
+    }
+
+    /// Runs the file I/O of the cache and the rate limiter on tokio's blocking threads,
+    /// so that it does not hold up other tasks.
+    async fn off_workers<R: Send + 'static>(
+        &self,
+        work: impl FnOnce(&ClientConfig) -> R + Send + 'static,
+    ) -> R {
+        let config = self.config.clone();
+        tokio::task::spawn_blocking(move || work(&config))
+            .await
+            .unwrap_or_else(|err| std::panic::resume_unwind(err.into_panic()))
+    }
+}
@@ -0,0 +1,257 @@
+/*! Sources of dictionary entries, so that lookups and rendering do not depend on the OD API
+ *
+ * A [DictionaryBackend] answers the few questions a lookup asks:
+ * the entry of a headword, the headwords of an inflected form, similar words, and audio.
+ * [lookup](DictionaryBackend::lookup) builds on them to resolve a word
+ * to its headwords and root forms the same way for every backend.
+ *
+ * Implemented by [OxdClient](crate::OxdClient), by
+ * [CacheBackend](crate::cache::CacheBackend) to answer from cached responses only,
+ * and by [MemoryBackend] for entries held in memory, e.g. test fixtures.
+ */
+
+use crate::error::{self, OxdError};
+use crate::lookup::{word_id, Lookup, LookupFilters, RootResolver, DEFAULT_ROOT_DEPTH};
+use crate::models::lemmas::Lemmatron;
+use crate::models::search::{SearchResult, SearchResults};
+use crate::models::RetrieveEntry;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// A source of entries in the shape of the [models](crate::models).
+///
+/// Missing words are reported as [NotFound](OxdError::NotFound),
+/// or [NotCached](OxdError::NotCached) by backends reading a cache.
+pub trait DictionaryBackend {
+    /// The entry of a headword, e.g. "rust".
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError>;
+
+    /// The headwords an inflected form belongs to, e.g. "rust" for "rusted".
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError>;
+
+    /// Headwords similar to `query`, e.g. to suggest words when a lookup finds nothing.
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError>;
+
+    /// An audio file such as [Pronunciation::audio_file](crate::models::Pronunciation::audio_file).
+    /// Backends without audio report every file as not found.
+    fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        Err(OxdError::NotFound(url.to_owned()))
+    }
+
+    /// Looks up a word together with its headwords and their root forms.
+    fn lookup(&self, word: &str) -> Result<Lookup, OxdError> {
+        self.lookup_with(word, &LookupFilters::default())
+    }
+
+    /// Like [lookup](Self::lookup), keeping only what matches `filters`.
+    ///
+    /// By default every entry is [pruned](LookupFilters::prune) after it is read;
+    /// backends that filter at the source may do better.
+    fn lookup_with(&self, word: &str, filters: &LookupFilters) -> Result<Lookup, OxdError> {
+        resolve(self, word, filters, DEFAULT_ROOT_DEPTH)
+    }
+}
+
+/// Looks up a word on any backend: resolves it to headwords through
+/// [lemmas](DictionaryBackend::lemmas), then follows root forms
+/// up to `max_depth` derivation steps. Missing headwords and roots are skipped.
+pub fn resolve<B: DictionaryBackend + ?Sized>(
+    backend: &B,
+    word: &str,
+    filters: &LookupFilters,
+    max_depth: usize,
+) -> Result<Lookup, OxdError> {
+    let mut resolver = RootResolver::start(word, backend.lemmas(word), max_depth)?;
+    while let Some(headword) = resolver.next() {
+        let entry = backend.entry(headword).map(|mut entry| {
+            filters.prune(&mut entry);
+            entry
+        });
+        resolver.found(entry)?;
+    }
+    resolver.finish()
+}
+
+/// Ranks `candidates` by how closely they are spelled like `query`,
+/// for backends that have no search of their own.
+///
+/// Candidates starting with the query, or a couple of typos away from it, match.
+pub(crate) fn similar_words<'a>(
+    query: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+    limit: usize,
+) -> SearchResults {
+    let query = query.trim().to_lowercase();
+    let mut results: Vec<SearchResult> = candidates
+        .into_iter()
+        .filter_map(|candidate| {
+            let lowered = candidate.to_lowercase();
+            let distance = if lowered.starts_with(&query) {
+                lowered.chars().count() - query.chars().count()
+            } else {
+                edit_distance(&query, &lowered)
+            };
+            let close = lowered.starts_with(&query) || distance <= query.chars().count().min(2);
+            close.then(|| SearchResult {
+                id: word_id(candidate),
+                word: candidate.to_owned(),
+                label: None,
+                match_string: Some(query.clone()),
+                match_type: Some("fuzzy".to_owned()),
+                region: None,
+                score: Some(1.0 / (1.0 + distance as f64)),
+            })
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        let (a_score, b_score) = (a.score.unwrap_or(0.0), b.score.unwrap_or(0.0));
+        b_score
+            .total_cmp(&a_score)
+            .then_with(|| a.word.cmp(&b.word))
+    });
+    results.dedup_by(|a, b| a.id == b.id);
+    results.truncate(limit);
+    SearchResults {
+        metadata: serde_json::Value::Null,
+        results,
+    }
+}
+
+/// Counts the insertions, deletions, substitutions and swaps of adjacent letters
+/// that turn one word into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let (a, b): (Vec<char>, Vec<char>) = (a.chars().collect(), b.chars().collect());
+    let mut rows = vec![vec![0; b.len() + 1]; a.len() + 1];
+    rows[0] = (0..=b.len()).collect();
+    for (i, row) in rows.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let substitution = rows[i - 1][j - 1] + usize::from(a[i - 1] != b[j - 1]);
+            let mut distance = substitution.min(rows[i - 1][j] + 1).min(rows[i][j - 1] + 1);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                distance = distance.min(rows[i - 2][j - 2] + 1);
+            }
+            rows[i][j] = distance;
+        }
+    }
+    rows[a.len()][b.len()]
+}
+
+/// Entries held in memory, e.g. fixtures for tests or tools working offline.
+///
+/// ```
+/// use oxd::backend::{DictionaryBackend, MemoryBackend};
+///
+/// let backend = MemoryBackend::new();
+/// assert!(backend.lookup("rust").is_err());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryBackend {
+    entries: BTreeMap<String, RetrieveEntry>,
+    lemmas: HashMap<String, Lemmatron>,
+    audio: HashMap<String, Vec<u8>>,
+}
+
+impl MemoryBackend {
+    pub fn new() -> Self {
+        MemoryBackend::default()
+    }
+
+    /// Reads responses saved from the OD API, laid out as `words/<word>.json`
+    /// and `lemmas/<word>.json` under `dir`. Either directory may be missing.
+    pub fn load_dir(dir: impl AsRef<Path>) -> Result<Self, OxdError> {
+        let dir = dir.as_ref();
+        let mut backend = MemoryBackend::new();
+        for (word, body) in read_responses(&dir.join("words"))? {
+            backend = backend.with_entry(&word, error::decode(&body)?);
+        }
+        for (word, body) in read_responses(&dir.join("lemmas"))? {
+            backend = backend.with_lemmas(&word, error::decode(&body)?);
+        }
+        Ok(backend)
+    }
+
+    /// Adds the entry of a headword.
+    pub fn with_entry(mut self, word: &str, entry: RetrieveEntry) -> Self {
+        self.entries.insert(word_id(word), entry);
+        self
+    }
+
+    /// Adds the lemmas of an inflected form.
+    pub fn with_lemmas(mut self, word: &str, lemmatron: Lemmatron) -> Self {
+        self.lemmas.insert(word_id(word), lemmatron);
+        self
+    }
+
+    /// Adds an audio file, served for its URL.
+    pub fn with_audio(mut self, url: impl Into<String>, audio: Vec<u8>) -> Self {
+        self.audio.insert(url.into(), audio);
+        self
+    }
+}
+
+/// Reads the `.json` files of a directory, keyed by file stem.
+fn read_responses(dir: &Path) -> Result<Vec<(String, String)>, OxdError> {
+    let listing = match fs::read_dir(dir) {
+        Ok(listing) => listing,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+        Err(source) => return Err(OxdError::io(dir, source)),
+    };
+    let mut responses = vec![];
+    for file in listing {
+        let path = file.map_err(|source| OxdError::io(dir, source))?.path();
+        if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            let body = fs::read_to_string(&path).map_err(|source| OxdError::io(&path, source))?;
+            let word = path.file_stem().unwrap_or_default().to_string_lossy();
+            responses.push((word.into_owned(), body));
+        }
+    }
+    Ok(responses)
+}
+
+impl DictionaryBackend for MemoryBackend {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        self.entries
+            .get(&word_id(word))
+            .cloned()
+            .ok_or_else(|| OxdError::NotFound(word.to_owned()))
+    }
+
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        self.lemmas
+            .get(&word_id(word))
+            .cloned()
+            .ok_or_else(|| OxdError::NotFound(word.to_owned()))
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        Ok(similar_words(
+            query,
+            self.entries.keys().map(String::as_str),
+            limit,
+        ))
+    }
+
+    fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        self.audio
+            .get(url)
+            .cloned()
+            .ok_or_else(|| OxdError::NotFound(url.to_owned()))
+    }
+}
+
+#[test]
+fn test_similar_words() {
+    let words = ["rust", "rusty", "trust", "crust", "iron"];
+    let results = similar_words("rsut", words, 5);
+    let found: Vec<_> = results.ranked().iter().map(|r| r.word.as_str()).collect();
+    assert_eq!(found, ["rust", "crust", "rusty", "trust"]);
+    assert_eq!(similar_words("rus", words, 1).results[0].word, "rust");
+    assert!(similar_words("zinc", words, 5).results.is_empty());
+}
@@ -0,0 +1,555 @@
+/*! Keeping API responses on disk between runs */
+
+use crate::backend::{self, DictionaryBackend};
+use crate::dirs;
+use crate::error::{self, OxdError};
+use crate::language::{Language, TranslationLanguage};
+use crate::lookup::{word_id, Lookup, LookupFilters, DEFAULT_ROOT_DEPTH};
+use crate::models::lemmas::Lemmatron;
+use crate::models::search::SearchResults;
+use crate::models::RetrieveEntry;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// What a cached response answers, e.g. the `words` endpoint for "rust" in `en-us`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct CacheKey {
+    /// The endpoint, e.g. `words` or `search/thesaurus`.
+    pub endpoint: String,
+    /// The dictionary, e.g. `en-us`, or `en/es` for translations.
+    pub language: String,
+    /// The word as it was sent.
+    pub word: String,
+    /// The other query parameters, sorted, e.g. `lexicalCategory=verb`.
+    pub filters: String,
+}
+
+impl CacheKey {
+    /// Describes the request for an endpoint path and query,
+    /// taking the word from `q` or else the last path segment.
+    pub(crate) fn new(segments: &[&str], query: &[(&str, &str)]) -> Self {
+        let (path, word) = match query.iter().find(|(name, _)| *name == "q") {
+            Some((_, word)) => (segments, *word),
+            None => match segments.split_last() {
+                Some((word, path)) => (path, *word),
+                None => (segments, ""),
+            },
+        };
+        let is_language = |segment: &&str| {
+            Language::CODES.contains(segment) || TranslationLanguage::CODES.contains(segment)
+        };
+        let split = path.iter().position(is_language).unwrap_or(path.len());
+        let mut filters: Vec<String> = query
+            .iter()
+            .filter(|(name, _)| *name != "q")
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect();
+        filters.sort();
+        CacheKey {
+            endpoint: path[..split].join("/"),
+            language: path[split..].join("/"),
+            word: word.to_owned(),
+            filters: filters.join("&"),
+        }
+    }
+
+    /// Describes a request for a utility endpoint such as `domains/en-us`, which names no word.
+    pub(crate) fn catalog(segments: &[&str]) -> Self {
+        let (endpoint, language) = segments.split_first().unwrap_or((&"", &[]));
+        CacheKey {
+            endpoint: (*endpoint).to_owned(),
+            language: language.join("/"),
+            word: String::new(),
+            filters: String::new(),
+        }
+    }
+
+    /// Whether the key could describe a request: the endpoint is made of plain path segments
+    /// and the language of known codes, with no `..`, leading `/` or backslash anywhere.
+    fn is_plausible(&self) -> bool {
+        let plain = |segment: &str| {
+            !segment.is_empty() && segment != "." && segment != ".." && !segment.contains('\\')
+        };
+        let is_language = |segment: &str| {
+            Language::CODES.contains(&segment) || TranslationLanguage::CODES.contains(&segment)
+        };
+        self.endpoint.split('/').all(plain)
+            && (self.language.is_empty() || self.language.split('/').all(is_language))
+    }
+
+    /// Where the response is stored, relative to the cache directory,
+    /// e.g. `words/en-us/rust.json`, or `domains/en-us/_.json` for a utility endpoint.
+    ///
+    /// Every component is escaped, so no key can name a file outside the cache.
+    fn file_name(&self) -> PathBuf {
+        let mut name = match self.word.as_str() {
+            "" => "_".to_owned(),
+            word => escape(word),
+        };
+        if !self.filters.is_empty() {
+            name.push_str(&format!("~{:016x}", fnv1a(&self.filters)));
+        }
+        let mut path: PathBuf = self
+            .endpoint
+            .split('/')
+            .chain(self.language.split('/'))
+            .map(|segment| match segment {
+                "" => "_".to_owned(),
+                segment => escape(segment),
+            })
+            .collect();
+        path.push(name + ".json");
+        path
+    }
+}
+
+/// A response read from the cache.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedResponse {
+    #[serde(flatten)]
+    pub key: CacheKey,
+    /// When the response was fetched, in seconds since the Unix epoch.
+    pub fetched_at: u64,
+    /// The response body exactly as the API sent it.
+    pub body: String,
+}
+
+impl CachedResponse {
+    /// How long ago the response was fetched.
+    pub fn age(&self) -> Duration {
+        Duration::from_secs(now().saturating_sub(self.fetched_at))
+    }
+}
+
+/// A directory of API responses, consulted before sending a request.
+///
+/// Only responses that decode successfully are stored, one file each,
+/// so errors such as unknown words are always asked again.
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    ttl: Option<Duration>,
+    refresh: bool,
+}
+
+impl ResponseCache {
+    /// Responses older than this are fetched again, unless [ttl](Self::ttl) says otherwise.
+    pub const DEFAULT_TTL: Duration = Duration::from_secs(30 * 24 * 60 * 60);
+
+    /// A cache in `dir`, created when the first response is stored.
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        ResponseCache {
+            dir: dir.into(),
+            ttl: Some(ResponseCache::DEFAULT_TTL),
+            refresh: false,
+        }
+    }
+
+    /// A cache in `$XDG_CACHE_HOME/oxd/responses`.
+    pub fn open_default() -> Result<Self, OxdError> {
+        Ok(ResponseCache::new(dirs::cache_dir()?.join("responses")))
+    }
+
+    /// Sets how long responses stay fresh, or `None` to keep them forever.
+    pub fn ttl(mut self, ttl: Option<Duration>) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    /// Ignores cached responses but still stores new ones, to bring the cache up to date.
+    pub fn refresh(mut self, refresh: bool) -> Self {
+        self.refresh = refresh;
+        self
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Reads a fresh response, or `None` when it is missing, expired or unreadable.
+    pub fn get(&self, key: &CacheKey) -> Option<CachedResponse> {
+        if self.refresh {
+            return None;
+        }
+        let cached = self.load(key)?;
+        match self.ttl {
+            Some(ttl) if cached.age() > ttl => None,
+            _ => Some(cached),
+        }
+    }
+
+    /// Reads a response whatever its age, or `None` when it is missing or unreadable.
+    pub fn load(&self, key: &CacheKey) -> Option<CachedResponse> {
+        let text = fs::read_to_string(self.dir.join(key.file_name())).ok()?;
+        serde_json::from_str(&text).ok()
+    }
+
+    /// Stores a response body fetched just now.
+    pub fn put(&self, key: &CacheKey, body: &str) -> Result<(), OxdError> {
+        self.write(&CachedResponse {
+            key: key.clone(),
+            fetched_at: now(),
+            body: body.to_owned(),
+        })
+    }
+
+    /// Every cached response with the file it was read from, sorted by key.
+    ///
+    /// Files that are not cached responses are left out.
+    pub fn list(&self) -> Result<Vec<CachedFile>, OxdError> {
+        let mut files = vec![];
+        cached_files(&self.dir, &mut files)?;
+        let mut responses: Vec<CachedFile> = files
+            .into_iter()
+            .filter_map(|(path, size)| {
+                let text = fs::read_to_string(&path).ok()?;
+                Some(CachedFile {
+                    response: serde_json::from_str(&text).ok()?,
+                    path,
+                    size,
+                })
+            })
+            .collect();
+        responses.sort_by(|a, b| {
+            let (a, b) = (&a.response.key, &b.response.key);
+            (&a.endpoint, &a.language, &a.word, &a.filters).cmp(&(
+                &b.endpoint,
+                &b.language,
+                &b.word,
+                &b.filters,
+            ))
+        });
+        Ok(responses)
+    }
+
+    /// Cached responses for `word` from any endpoint, e.g. both "Ice cream" and `ice_cream`.
+    pub fn find(&self, word: &str) -> Result<Vec<CachedResponse>, OxdError> {
+        let id = word_id(word);
+        Ok(self
+            .list()?
+            .into_iter()
+            .map(|file| file.response)
+            .filter(|cached| word_id(&cached.key.word) == id)
+            .collect())
+    }
+
+    /// Counts cached responses and their size.
+    pub fn stats(&self) -> Result<CacheStats, OxdError> {
+        let mut stats = CacheStats::default();
+        for file in self.list()? {
+            stats.responses += 1;
+            stats.size += file.size;
+            *stats
+                .per_language
+                .entry(file.response.key.language)
+                .or_default() += 1;
+        }
+        Ok(stats)
+    }
+
+    /// Removes responses older than `older_than`, or all of them,
+    /// and returns how many were removed.
+    pub fn purge(&self, older_than: Option<Duration>) -> Result<usize, OxdError> {
+        let mut removed = 0;
+        for file in self.list()? {
+            if older_than.is_none_or(|age| file.response.age() > age) {
+                match fs::remove_file(&file.path) {
+                    Ok(()) => removed += 1,
+                    // Another process got there first.
+                    Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+                    Err(source) => return Err(OxdError::io(&file.path, source)),
+                }
+            }
+        }
+        Ok(removed)
+    }
+
+    /// Writes every cached response to the file `archive`, one JSON object per line,
+    /// and returns how many were written.
+    pub fn export(&self, archive: impl AsRef<Path>) -> Result<usize, OxdError> {
+        let path = archive.as_ref();
+        let file = File::create(path).map_err(|source| OxdError::io(path, source))?;
+        let mut archive = BufWriter::new(file);
+        let responses = self.list()?;
+        for file in &responses {
+            let line =
+                serde_json::to_string(&file.response).expect("cached responses serialize to JSON");
+            writeln!(archive, "{line}").map_err(|source| OxdError::io(path, source))?;
+        }
+        archive
+            .flush()
+            .map_err(|source| OxdError::io(path, source))?;
+        Ok(responses.len())
+    }
+
+    /// Adds the responses of an [export](Self::export)ed archive and returns how many
+    /// were added. Responses already cached are only replaced by newer ones.
+    pub fn import(&self, archive: impl AsRef<Path>) -> Result<usize, OxdError> {
+        let path = archive.as_ref();
+        let file = File::open(path).map_err(|source| OxdError::io(path, source))?;
+        let mut imported = 0;
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let line = line.map_err(|source| OxdError::io(path, source))?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let cached: CachedResponse =
+                serde_json::from_str(&line).map_err(|source| OxdError::Decode {
+                    path: format!("{}, line {}", path.display(), number + 1),
+                    source,
+                })?;
+            if !cached.key.is_plausible() {
+                return Err(OxdError::io(
+                    path,
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "line {} caches {}/{}, which is not an endpoint and language",
+                            number + 1,
+                            cached.key.endpoint,
+                            cached.key.language
+                        ),
+                    ),
+                ));
+            }
+            let newer = match self.load(&cached.key) {
+                Some(existing) => existing.fetched_at < cached.fetched_at,
+                None => true,
+            };
+            if newer {
+                self.write(&cached)?;
+                imported += 1;
+            }
+        }
+        Ok(imported)
+    }
+
+    fn write(&self, cached: &CachedResponse) -> Result<(), OxdError> {
+        let path = self.dir.join(cached.key.file_name());
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|source| OxdError::io(dir, source))?;
+        }
+        let text = serde_json::to_string(cached).expect("cached responses serialize to JSON");
+        // Written through a temporary file, so other processes never read half a response.
+        static WRITES: AtomicUsize = AtomicUsize::new(0);
+        let temporary = path.with_extension(format!(
+            "json.{}-{}.tmp",
+            std::process::id(),
+            WRITES.fetch_add(1, Ordering::Relaxed)
+        ));
+        fs::write(&temporary, text).map_err(|source| OxdError::io(&temporary, source))?;
+        fs::rename(&temporary, &path).map_err(|source| {
+            let _ = fs::remove_file(&temporary);
+            OxdError::io(&path, source)
+        })
+    }
+}
+
+/// A cached response and the file it was read from.
+#[derive(Debug, Clone)]
+pub struct CachedFile {
+    pub response: CachedResponse,
+    pub path: PathBuf,
+    /// The size of the file, in bytes.
+    pub size: u64,
+}
+
+/// How much a [ResponseCache] holds.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CacheStats {
+    pub responses: usize,
+    /// Total size of the cached files, in bytes.
+    pub size: u64,
+    /// Responses per dictionary, e.g. `en-us`.
+    pub per_language: BTreeMap<String, usize>,
+}
+
+/// Collects the `.json` files under `dir` with their sizes. A missing directory is empty.
+fn cached_files(dir: &Path, files: &mut Vec<(PathBuf, u64)>) -> Result<(), OxdError> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(source) => return Err(OxdError::io(dir, source)),
+    };
+    for entry in entries {
+        let entry = entry.map_err(|source| OxdError::io(dir, source))?;
+        let path = entry.path();
+        let metadata = entry
+            .metadata()
+            .map_err(|source| OxdError::io(&path, source))?;
+        if metadata.is_dir() {
+            cached_files(&path, files)?;
+        } else if path
+            .extension()
+            .is_some_and(|extension| extension == "json")
+        {
+            files.push((path, metadata.len()));
+        }
+    }
+    Ok(())
+}
+
+/// Answers lookups from the responses of one dictionary in a [ResponseCache],
+/// whatever their age, without connecting to the API.
+///
+/// Words that were never fetched are [NotCached](OxdError::NotCached).
+#[derive(Debug, Clone)]
+pub struct CacheBackend {
+    cache: ResponseCache,
+    source_lang: Language,
+}
+
+impl ResponseCache {
+    /// Reads the cached responses of the `source_lang` dictionary as a [DictionaryBackend].
+    pub fn backend(&self, source_lang: Language) -> CacheBackend {
+        CacheBackend {
+            cache: self.clone(),
+            source_lang,
+        }
+    }
+}
+
+impl CacheBackend {
+    /// Decodes the cached response to a request made by the clients.
+    fn load<T: DeserializeOwned>(
+        &self,
+        segments: &[&str],
+        query: &[(&str, &str)],
+        word: &str,
+    ) -> Result<T, OxdError> {
+        let key = CacheKey::new(segments, query);
+        let cached = self
+            .cache
+            .load(&key)
+            .ok_or_else(|| OxdError::NotCached(word.to_owned()))?;
+        error::decode(&cached.body)
+    }
+}
+
+impl DictionaryBackend for CacheBackend {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        self.load(&["words", self.source_lang.code()], &[("q", word)], word)
+    }
+
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        let id = word_id(word);
+        self.load(&["lemmas", self.source_lang.dataset_code(), &id], &[], word)
+    }
+
+    /// A cached search for `query`, or else the cached headwords spelled like it.
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        let limit_param = limit.to_string();
+        let segments = ["search", self.source_lang.code()];
+        match self.load(&segments, &[("q", query), ("limit", &limit_param)], query) {
+            Err(OxdError::NotCached(_)) => {}
+            searched => return searched,
+        }
+        let responses = self.cache.list()?;
+        let words = responses.iter().filter_map(|file| {
+            let key = &file.response.key;
+            (key.endpoint == "words" && key.language == self.source_lang.code())
+                .then_some(key.word.as_str())
+        });
+        Ok(backend::similar_words(query, words, limit))
+    }
+
+    fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        Err(OxdError::NotCached(url.to_owned()))
+    }
+
+    fn lookup_with(&self, word: &str, filters: &LookupFilters) -> Result<Lookup, OxdError> {
+        match backend::resolve(self, word, filters, DEFAULT_ROOT_DEPTH) {
+            Err(OxdError::NotFound(word)) => Err(OxdError::NotCached(word)),
+            lookup => lookup,
+        }
+    }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Percent-encodes whatever is not safe in a file name.
+fn escape(word: &str) -> String {
+    let mut escaped = String::new();
+    for byte in word.bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'-' | b'_' => escaped.push(byte as char),
+            _ => escaped.push_str(&format!("%{byte:02X}")),
+        }
+    }
+    escaped
+}
+
+/// A hash that stays the same between runs, unlike the standard library's.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[test]
+fn test_file_name() {
+    let key = CacheKey::new(&["search", "thesaurus", "en"], &[("q", "ice cream")]);
+    assert_eq!(
+        key.file_name(),
+        Path::new("search/thesaurus/en/ice%20cream.json")
+    );
+    let key = CacheKey::catalog(&["domains", "en-us"]);
+    assert_eq!(key.file_name(), Path::new("domains/en-us/_.json"));
+    assert_eq!(
+        CacheKey::catalog(&["filters"]).file_name(),
+        Path::new("filters/_/_.json")
+    );
+    let key = CacheKey {
+        endpoint: "../../x".to_owned(),
+        language: "/etc".to_owned(),
+        word: "passwd".to_owned(),
+        filters: String::new(),
+    };
+    assert_eq!(
+        key.file_name(),
+        Path::new("%2E%2E/%2E%2E/x/_/etc/passwd.json")
+    );
+}
+
+#[test]
+fn test_cache_key() {
+    let key = CacheKey::new(
+        &["words", "en-us"],
+        &[("q", "rust"), ("lexicalCategory", "verb")],
+    );
+    assert_eq!(key.endpoint, "words");
+    assert_eq!(key.language, "en-us");
+    assert_eq!(key.word, "rust");
+    assert_eq!(key.filters, "lexicalCategory=verb");
+
+    let key = CacheKey::new(&["translations", "en", "es", "ice_cream"], &[]);
+    assert_eq!(key.endpoint, "translations");
+    assert_eq!(key.language, "en/es");
+    assert_eq!(key.word, "ice_cream");
+    assert_eq!(
+        key.file_name(),
+        Path::new("translations/en/es/ice_cream.json")
+    );
+
+    let key = CacheKey::new(
+        &["search", "thesaurus", "en-gb"],
+        &[("q", "../x y"), ("limit", "5")],
+    );
+    assert_eq!(key.endpoint, "search/thesaurus");
+    let file_name = key.file_name();
+    assert!(file_name
+        .to_str()
+        .unwrap()
+        .starts_with("search/thesaurus/en-gb/%2E%2E%2Fx%20y~"));
+}
@@ -0,0 +1,108 @@
+//! `oxd cache`: inspecting, cleaning up and moving cached responses.
+
+use crate::args::CacheAction;
+use oxd::cache::{CachedResponse, ResponseCache};
+use oxd::display::Display;
+use oxd::models::lemmas::Lemmatron;
+use oxd::models::search::SearchResults;
+use oxd::models::thesaurus::Thesaurus;
+use oxd::models::RetrieveEntry;
+use oxd::OxdError;
+use serde::de::DeserializeOwned;
+use std::time::Duration;
+
+/// Seconds in a day.
+const DAY: u64 = 24 * 60 * 60;
+
+pub fn run(action: &CacheAction) -> Result<(), OxdError> {
+    let cache = ResponseCache::open_default()?;
+    match action {
+        CacheAction::List => {
+            for file in cache.list()? {
+                let key = &file.response.key;
+                print!("{}  {}  {}", key.endpoint, key.language, key.word);
+                if !key.filters.is_empty() {
+                    print!("  [{}]", key.filters);
+                }
+                println!("  {}", describe_age(file.response.age()));
+            }
+        }
+        CacheAction::Show { word, json } => {
+            let found = cache.find(word)?;
+            if found.is_empty() {
+                return Err(OxdError::NotCached(word.clone()));
+            }
+            for cached in found {
+                match render(&cached).filter(|_| !json) {
+                    Some(canvas) => println!("{canvas}"),
+                    None => println!("{}", cached.body),
+                }
+            }
+        }
+        CacheAction::Purge { older_than } => {
+            let older_than = older_than.map(|days| Duration::from_secs(days.saturating_mul(DAY)));
+            let removed = cache.purge(older_than)?;
+            println!("Removed {removed} cached responses.");
+        }
+        CacheAction::Stats => {
+            let stats = cache.stats()?;
+            println!(
+                "{} cached responses, {} in {}",
+                stats.responses,
+                describe_size(stats.size),
+                cache.dir().display()
+            );
+            for (language, count) in &stats.per_language {
+                println!("  {language:<8}{count:>6}");
+            }
+        }
+        CacheAction::Export { file } => {
+            let exported = cache.export(file)?;
+            println!(
+                "Exported {exported} cached responses to {}.",
+                file.display()
+            );
+        }
+        CacheAction::Import { file } => {
+            let imported = cache.import(file)?;
+            println!("Imported {imported} cached responses.");
+        }
+    }
+    Ok(())
+}
+
+/// Renders a cached response like a lookup would, if its endpoint has a display.
+fn render(cached: &CachedResponse) -> Option<String> {
+    match cached.key.endpoint.as_str() {
+        "words" | "translations" => render_as::<RetrieveEntry>(&cached.body),
+        "thesaurus" => render_as::<Thesaurus>(&cached.body),
+        "lemmas" => render_as::<Lemmatron>(&cached.body),
+        "search" | "search/thesaurus" => render_as::<SearchResults>(&cached.body),
+        _ => None,
+    }
+}
+
+fn render_as<T: DeserializeOwned + Display>(body: &str) -> Option<String> {
+    let decoded: T = serde_json::from_str(body).ok()?;
+    let mut canvas = String::new();
+    decoded.display(&mut canvas);
+    Some(canvas)
+}
+
+fn describe_age(age: Duration) -> String {
+    let secs = age.as_secs();
+    match secs {
+        0..=59 => "just now".to_owned(),
+        60..=3599 => format!("{} minutes ago", secs / 60),
+        3600..=86_399 => format!("{} hours ago", secs / 3600),
+        _ => format!("{} days ago", secs / 86_400),
+    }
+}
+
+fn describe_size(bytes: u64) -> String {
+    match bytes {
+        0..=1023 => format!("{bytes} B"),
+        1024..=1_048_575 => format!("{:.1} KiB", bytes as f64 / 1024.0),
+        _ => format!("{:.1} MiB", bytes as f64 / 1_048_576.0),
+    }
+}
@@ -0,0 +1,784 @@
+/*! A configurable client for the OD API */
+
+use crate::cache::{CacheKey, ResponseCache};
+use crate::error::{self, OxdError};
+use crate::language::{Language, TranslationLanguage};
+use crate::lookup::{word_id, LookupFilters, DEFAULT_ROOT_DEPTH};
+use crate::models::catalog::LabelKind;
+use crate::rate_limit::RateLimiter;
+use crate::retry::RetryPolicy;
+use reqwest::header::{self, HeaderValue};
+use reqwest::{Proxy, Url};
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "blocking")]
+use {
+    crate::backend::{self, DictionaryBackend},
+    crate::lookup::Lookup,
+    crate::models::{
+        catalog::{EndpointOptions, Labels, Languages},
+        lemmas::Lemmatron,
+        search::SearchResults,
+        sentences::Sentences,
+        thesaurus::Thesaurus,
+        RetrieveEntry,
+    },
+    reqwest::blocking,
+    std::sync::atomic::{AtomicUsize, Ordering},
+    std::thread,
+};
+
+const OD_API_BASE_URL: &str = "https://od-api.oxforddictionaries.com/api/v2/";
+
+/// A blocking client for the OD API, created with [OxdClient::builder].
+///
+/// The client keeps a connection pool, so build it once and reuse it.
+#[cfg(feature = "blocking")]
+#[derive(Debug, Clone)]
+pub struct OxdClient {
+    http: blocking::Client,
+    config: ClientConfig,
+}
+
+/// Settings shared by [OxdClient] and [AsyncOxdClient](crate::AsyncOxdClient).
+#[derive(Debug, Clone)]
+pub(crate) struct ClientConfig {
+    /// Headers carrying the credentials, sent with every API request.
+    pub(crate) credentials: header::HeaderMap,
+    pub(crate) base_url: Url,
+    pub(crate) source_lang: Language,
+    pub(crate) catalog: Catalog,
+    pub(crate) retry: RetryPolicy,
+    pub(crate) rate_limiter: Option<RateLimiter>,
+    pub(crate) cache: Option<ResponseCache>,
+    pub(crate) offline: bool,
+    /// How many derivation steps to follow from headwords to root forms.
+    pub(crate) root_depth: usize,
+}
+
+impl ClientConfig {
+    /// Decodes a fresh cached response, if caching is on and there is one.
+    ///
+    /// A response the models no longer decode is fetched again.
+    pub(crate) fn cached<T: DeserializeOwned>(&self, key: &CacheKey) -> Option<T> {
+        let cache = self.cache.as_ref()?;
+        let cached = if self.offline {
+            cache.load(key)?
+        } else {
+            cache.get(key)?
+        };
+        error::decode(&cached.body).ok()
+    }
+
+    /// Fails with [NotCached](OxdError::NotCached) when the client may not go online.
+    pub(crate) fn check_online(&self, word: &str) -> Result<(), OxdError> {
+        if self.offline {
+            return Err(OxdError::NotCached(word.to_owned()));
+        }
+        Ok(())
+    }
+
+    /// What [entries](OxdClient::entries) reports when it finds nothing.
+    pub(crate) fn nothing_found(&self, word: &str) -> OxdError {
+        if self.offline {
+            OxdError::NotCached(word.to_owned())
+        } else {
+            OxdError::NotFound(word.to_owned())
+        }
+    }
+
+    /// Stores a response body, if caching is on.
+    ///
+    /// Caching is best-effort: a response that cannot be written,
+    /// e.g. to a full disk, is still as good an answer.
+    pub(crate) fn store(&self, key: &CacheKey, body: &str) {
+        if let Some(cache) = &self.cache {
+            let _ = cache.put(key, body);
+        }
+    }
+
+    /// The answer to a utility endpoint this client fetched before, if any.
+    pub(crate) fn remembered<T: DeserializeOwned>(
+        &self,
+        request: &Request,
+    ) -> Option<Result<T, OxdError>> {
+        if !request.catalog {
+            return None;
+        }
+        let body = self.catalog.lock().unwrap().get(&request.key).cloned()?;
+        Some(error::decode(&body))
+    }
+
+    /// Decodes a response just fetched, then stores it in the cache
+    /// and, for a utility endpoint, remembers it.
+    pub(crate) fn answer<T: DeserializeOwned>(
+        &self,
+        request: &Request,
+        body: String,
+    ) -> Result<T, OxdError> {
+        let decoded = error::decode(&body)?;
+        self.store(&request.key, &body);
+        if request.catalog {
+            self.catalog
+                .lock()
+                .unwrap()
+                .insert(request.key.clone(), body);
+        }
+        Ok(decoded)
+    }
+
+    /// A request for the entry of a headword.
+    pub(crate) fn entry_request(&self, word: &str, filters: &LookupFilters) -> Request {
+        let params = filters.query();
+        let mut query = vec![("q", word)];
+        query.extend(params.iter().map(|(name, value)| (*name, value.as_str())));
+        self.request(&["words", self.source_lang.code()], &query, word)
+    }
+
+    pub(crate) fn lemmas_request(&self, word: &str) -> Request {
+        let id = word_id(word);
+        self.request(&["lemmas", self.source_lang.dataset_code(), &id], &[], word)
+    }
+
+    pub(crate) fn search_request(&self, query: &str, limit: usize) -> Request {
+        let limit = limit.to_string();
+        let params = [("q", query), ("limit", limit.as_str())];
+        self.request(&["search", self.source_lang.code()], &params, query)
+    }
+
+    pub(crate) fn search_thesaurus_request(&self, query: &str, limit: usize) -> Request {
+        let limit = limit.to_string();
+        let params = [("q", query), ("limit", limit.as_str())];
+        let segments = ["search", "thesaurus", self.source_lang.dataset_code()];
+        self.request(&segments, &params, query)
+    }
+
+    pub(crate) fn sentences_request(&self, word: &str) -> Request {
+        let id = word_id(word);
+        self.request(
+            &["sentences", self.source_lang.dataset_code(), &id],
+            &[],
+            word,
+        )
+    }
+
+    pub(crate) fn translations_request(
+        &self,
+        source: TranslationLanguage,
+        target: TranslationLanguage,
+        word: &str,
+    ) -> Request {
+        let id = word_id(word);
+        self.request(
+            &["translations", source.code(), target.code(), &id],
+            &[],
+            word,
+        )
+    }
+
+    pub(crate) fn thesaurus_request(&self, word: &str) -> Request {
+        let id = word_id(word);
+        self.request(
+            &["thesaurus", self.source_lang.dataset_code(), &id],
+            &[],
+            word,
+        )
+    }
+
+    pub(crate) fn labels_request(&self, kind: LabelKind) -> Request {
+        self.catalog_request(&[kind.endpoint(), self.source_lang.code()])
+    }
+
+    /// A request for a utility endpoint that lists what the API offers, e.g. `filters`.
+    pub(crate) fn catalog_request(&self, segments: &[&str]) -> Request {
+        Request {
+            url: endpoint_url(&self.base_url, segments, &[]),
+            key: CacheKey::catalog(segments),
+            word: segments.join("/"),
+            catalog: true,
+        }
+    }
+
+    /// A request for an endpoint. Path segments are percent-encoded,
+    /// so words can be passed as they are.
+    fn request(&self, segments: &[&str], query: &[(&str, &str)], word: &str) -> Request {
+        Request {
+            url: endpoint_url(&self.base_url, segments, query),
+            key: CacheKey::new(segments, query),
+            word: word.to_owned(),
+            catalog: false,
+        }
+    }
+}
+
+/// A GET request to an endpoint, prepared the same way by both clients,
+/// which only differ in how they send it.
+#[derive(Debug, Clone)]
+pub(crate) struct Request {
+    pub(crate) url: Url,
+    /// Where the response is kept in the [ResponseCache].
+    pub(crate) key: CacheKey,
+    /// The word errors name, e.g. "rust".
+    pub(crate) word: String,
+    /// Whether the endpoint is a utility endpoint, remembered for the life of the client.
+    catalog: bool,
+}
+
+/// Responses of the utility endpoints fetched by a client.
+///
+/// They rarely change, so each is fetched once per client and shared by its clones,
+/// and kept in the [ResponseCache] as well, if there is one.
+pub(crate) type Catalog = Arc<Mutex<HashMap<CacheKey, String>>>;
+
+/// Entries fetched while looking up many words, keyed by headword.
+///
+/// Each slot is `None` until fetched, then holds the entry or `None` if it is missing.
+#[cfg(feature = "blocking")]
+type EntryMemo = Mutex<HashMap<String, Arc<Mutex<Option<Option<RetrieveEntry>>>>>>;
+
+/// Configures an [OxdClient] or an [AsyncOxdClient](crate::AsyncOxdClient).
+#[derive(Debug, Clone)]
+pub struct OxdClientBuilder {
+    app_id: Option<String>,
+    app_key: Option<String>,
+    base_url: String,
+    source_lang: Language,
+    timeout: Option<Duration>,
+    connect_timeout: Option<Duration>,
+    user_agent: Option<String>,
+    proxy: Option<Proxy>,
+    retry: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    cache: Option<ResponseCache>,
+    offline: bool,
+    root_depth: usize,
+}
+
+impl Default for OxdClientBuilder {
+    fn default() -> Self {
+        OxdClientBuilder {
+            app_id: None,
+            app_key: None,
+            base_url: OD_API_BASE_URL.to_owned(),
+            source_lang: Language::default(),
+            timeout: None,
+            connect_timeout: None,
+            user_agent: None,
+            proxy: None,
+            retry: RetryPolicy::never(),
+            rate_limiter: None,
+            cache: None,
+            offline: false,
+            root_depth: DEFAULT_ROOT_DEPTH,
+        }
+    }
+}
+
+impl OxdClientBuilder {
+    /// Sets the application id obtained from the OD API website.
+    pub fn app_id(mut self, app_id: impl Into<String>) -> Self {
+        self.app_id = Some(app_id.into());
+        self
+    }
+
+    /// Sets the application key obtained from the OD API website.
+    pub fn app_key(mut self, app_key: impl Into<String>) -> Self {
+        self.app_key = Some(app_key.into());
+        self
+    }
+
+    /// Points the client at another server, e.g. a mock server in tests.
+    pub fn base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    /// Sets the dictionary language, `en-us` by default.
+    pub fn source_lang(mut self, source_lang: Language) -> Self {
+        self.source_lang = source_lang;
+        self
+    }
+
+    /// Sets a timeout for whole requests.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets a timeout for establishing connections.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the `User-Agent` header sent with every request.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Sends every request through a proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.proxy = Some(proxy);
+        self
+    }
+
+    /// Retries requests that fail for a transient reason, never by default.
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Keeps requests within the quotas of a plan. Clients built with clones
+    /// of one limiter share its budget.
+    pub fn rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Answers lookups from a cache of earlier responses when it can,
+    /// and stores new responses in it. Off by default.
+    pub fn cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Answers only from the [cache](Self::cache), whatever the age of its responses,
+    /// and never connects to the API. Anything not cached fails with
+    /// [NotCached](OxdError::NotCached).
+    pub fn offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Follows root forms up to `depth` derivation steps from the headwords
+    /// when looking up [entries](OxdClient::entries), 3 by default; 0 skips them.
+    pub fn root_depth(mut self, depth: usize) -> Self {
+        self.root_depth = depth;
+        self
+    }
+
+    /// Builds a blocking client, checking that credentials and base URL are usable.
+    #[cfg(feature = "blocking")]
+    pub fn build(self) -> Result<OxdClient, OxdError> {
+        let config = self.config()?;
+        let mut http = blocking::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+        let http = http.build().map_err(|err| config_error(err.to_string()))?;
+
+        Ok(OxdClient { http, config })
+    }
+
+    /// Builds an async client, checking that credentials and base URL are usable.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<crate::AsyncOxdClient, OxdError> {
+        let config = self.config()?;
+        let mut http = reqwest::Client::builder();
+        if let Some(timeout) = self.timeout {
+            http = http.timeout(timeout);
+        }
+        if let Some(timeout) = self.connect_timeout {
+            http = http.connect_timeout(timeout);
+        }
+        if let Some(user_agent) = self.user_agent {
+            http = http.user_agent(user_agent);
+        }
+        if let Some(proxy) = self.proxy {
+            http = http.proxy(proxy);
+        }
+        let http = http.build().map_err(|err| config_error(err.to_string()))?;
+
+        Ok(crate::AsyncOxdClient::new(http, config))
+    }
+
+    /// Checks the settings that do not depend on the HTTP client.
+    fn config(&self) -> Result<ClientConfig, OxdError> {
+        if self.offline && self.cache.is_none() {
+            return Err(config_error("offline mode needs a cache"));
+        }
+        Ok(ClientConfig {
+            credentials: self.credentials()?,
+            base_url: parse_base_url(&self.base_url)?,
+            source_lang: self.source_lang,
+            catalog: Catalog::default(),
+            retry: self.retry.clone(),
+            rate_limiter: self.rate_limiter.clone(),
+            cache: self.cache.clone(),
+            offline: self.offline,
+            root_depth: self.root_depth,
+        })
+    }
+
+    /// Headers carrying the credentials, sent with every API request.
+    fn credentials(&self) -> Result<header::HeaderMap, OxdError> {
+        let app_id = self
+            .app_id
+            .as_ref()
+            .ok_or_else(|| config_error("missing app_id"))?;
+        let app_key = self
+            .app_key
+            .as_ref()
+            .ok_or_else(|| config_error("missing app_key"))?;
+        let mut headers = header::HeaderMap::new();
+        headers.insert("app_id", header_value(app_id)?);
+        headers.insert("app_key", header_value(app_key)?);
+        Ok(headers)
+    }
+}
+
+fn config_error(message: impl Into<String>) -> OxdError {
+    OxdError::InvalidConfig(message.into())
+}
+
+fn header_value(value: &str) -> Result<HeaderValue, OxdError> {
+    HeaderValue::from_str(value).map_err(|err| config_error(err.to_string()))
+}
+
+/// Parses the base URL so that endpoint paths can be appended to it.
+fn parse_base_url(base_url: &str) -> Result<Url, OxdError> {
+    let url =
+        Url::parse(base_url).map_err(|err| config_error(format!("invalid base URL: {err}")))?;
+    if url.cannot_be_a_base() {
+        return Err(config_error(format!("invalid base URL: {base_url}")));
+    }
+    Ok(url)
+}
+
+/// Appends percent-encoded path segments and a query to the base URL.
+fn endpoint_url(base_url: &Url, segments: &[&str], query: &[(&str, &str)]) -> Url {
+    let mut url = base_url.clone();
+    url.path_segments_mut()
+        .expect("base URL is checked when building the client")
+        .pop_if_empty()
+        .extend(segments);
+    if !query.is_empty() {
+        url.query_pairs_mut().extend_pairs(query);
+    }
+    url
+}
+
+#[cfg(feature = "blocking")]
+impl OxdClient {
+    /// Starts configuring a client.
+    pub fn builder() -> OxdClientBuilder {
+        OxdClientBuilder::default()
+    }
+
+    /// The dictionary language this client queries.
+    pub fn source_lang(&self) -> Language {
+        self.config.source_lang
+    }
+
+    /// Queries the API and returns a single [RetrieveEntry](crate::models::RetrieveEntry).
+    pub fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        self.entry_with(word, &LookupFilters::default())
+    }
+
+    /// Like [entry](Self::entry), keeping only what matches `filters`.
+    ///
+    /// Whatever the endpoint does not filter itself is [pruned](LookupFilters::prune),
+    /// and an entry left empty is reported as [NotFound](OxdError::NotFound).
+    pub fn entry_with(
+        &self,
+        word: &str,
+        filters: &LookupFilters,
+    ) -> Result<RetrieveEntry, OxdError> {
+        let entry = self.get(self.config.entry_request(word, filters))?;
+        filters.narrow(entry, word)
+    }
+
+    /// Looks up a word together with its headwords and their root forms.
+    ///
+    /// The lemmas endpoint resolves inflected forms first,
+    /// so "running" finds "run" and "mice" finds "mouse".
+    /// Root forms are followed up to [root_depth](OxdClientBuilder::root_depth) steps,
+    /// fetching each once. Headwords and root forms missing from the API, or from the cache
+    /// when [offline](OxdClientBuilder::offline), are skipped.
+    pub fn entries(&self, word: &str) -> Result<Lookup, OxdError> {
+        self.entries_with(word, &LookupFilters::default())
+    }
+
+    /// Like [entries](Self::entries), keeping only what matches `filters`
+    /// in the entries of headwords and root forms alike.
+    pub fn entries_with(&self, word: &str, filters: &LookupFilters) -> Result<Lookup, OxdError> {
+        self.lookup(word, filters, None)
+    }
+
+    /// Looks up many words at once like [entries](Self::entries),
+    /// on up to `concurrency` threads sharing this client.
+    ///
+    /// Root forms shared by several words, such as "rust" for "rusty" and "rusted",
+    /// are fetched once. Results come back in the order of `words`,
+    /// each with its own error.
+    pub fn get_many<S: AsRef<str> + Sync>(
+        &self,
+        words: &[S],
+        concurrency: usize,
+    ) -> Vec<Result<Lookup, OxdError>> {
+        self.get_many_with(words, &LookupFilters::default(), concurrency)
+    }
+
+    /// Like [get_many](Self::get_many), keeping only what matches `filters`.
+    pub fn get_many_with<S: AsRef<str> + Sync>(
+        &self,
+        words: &[S],
+        filters: &LookupFilters,
+        concurrency: usize,
+    ) -> Vec<Result<Lookup, OxdError>> {
+        let memo = EntryMemo::default();
+        let next = AtomicUsize::new(0);
+        let results: Vec<Mutex<Option<Result<Lookup, OxdError>>>> =
+            words.iter().map(|_| Mutex::new(None)).collect();
+        thread::scope(|scope| {
+            for _ in 0..concurrency.clamp(1, words.len().max(1)) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(word) = words.get(i) else {
+                        break;
+                    };
+                    let lookup = self.lookup(word.as_ref(), filters, Some(&memo));
+                    *results[i].lock().unwrap() = Some(lookup);
+                });
+            }
+        });
+        results
+            .into_iter()
+            .map(|result| {
+                result
+                    .into_inner()
+                    .unwrap()
+                    .expect("every word is looked up")
+            })
+            .collect()
+    }
+
+    /// Queries the lemmas endpoint, which links inflected forms to their headwords.
+    pub fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        self.get(self.config.lemmas_request(word))
+    }
+
+    /// Searches headwords similar to `query`, e.g. to suggest words
+    /// when [entries](Self::entries) finds nothing.
+    pub fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        self.get(self.config.search_request(query, limit))
+    }
+
+    /// Searches headwords of the thesaurus similar to `query`.
+    pub fn search_thesaurus(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        self.get(self.config.search_thesaurus_request(query, limit))
+    }
+
+    /// Queries corpus sentences, linked to senses by [Sense::id](crate::models::Sense::id).
+    ///
+    /// Use [attach_sentences](crate::models::sentences::attach_sentences) to show them under
+    /// the senses of an entry.
+    pub fn sentences(&self, word: &str) -> Result<Sentences, OxdError> {
+        self.get(self.config.sentences_request(word))
+    }
+
+    /// Queries a bilingual dictionary and returns an entry whose
+    /// [senses](crate::models::Sense) carry [translations](crate::models::Translation).
+    pub fn translations(
+        &self,
+        source: TranslationLanguage,
+        target: TranslationLanguage,
+        word: &str,
+    ) -> Result<RetrieveEntry, OxdError> {
+        self.get(self.config.translations_request(source, target, word))
+    }
+
+    /// Queries the thesaurus and returns synonyms and antonyms grouped by sense.
+    pub fn thesaurus(&self, word: &str) -> Result<Thesaurus, OxdError> {
+        self.get(self.config.thesaurus_request(word))
+    }
+
+    /// Lists the valid values of a label in the source language, e.g. every domain.
+    pub fn labels(&self, kind: LabelKind) -> Result<Labels, OxdError> {
+        self.get(self.config.labels_request(kind))
+    }
+
+    /// Lists the monolingual and bilingual dictionaries the API offers.
+    pub fn languages(&self) -> Result<Languages, OxdError> {
+        self.get(self.config.catalog_request(&["languages"]))
+    }
+
+    /// Lists the fields each endpoint can return.
+    pub fn fields(&self) -> Result<EndpointOptions, OxdError> {
+        self.get(self.config.catalog_request(&["fields"]))
+    }
+
+    /// Lists the filters each endpoint accepts.
+    pub fn filters(&self) -> Result<EndpointOptions, OxdError> {
+        self.get(self.config.catalog_request(&["filters"]))
+    }
+
+    /// Downloads an audio file such as [Pronunciation::audio_file](crate::models::Pronunciation::audio_file).
+    ///
+    /// Credentials are not sent along, as audio files are hosted elsewhere.
+    pub fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        self.config.check_online(url)?;
+        let res = self.http.get(url).send()?;
+        if let Some(err) = OxdError::from_status(res.status(), res.headers(), url) {
+            return Err(err);
+        }
+        Ok(res.bytes()?.to_vec())
+    }
+
+    /// Resolves a word to its headwords and their root forms,
+    /// sharing entries through `memo` when looking up many words.
+    fn lookup(
+        &self,
+        word: &str,
+        filters: &LookupFilters,
+        memo: Option<&EntryMemo>,
+    ) -> Result<Lookup, OxdError> {
+        let headwords = Headwords {
+            client: self,
+            filters,
+            memo,
+        };
+        // Entries come filtered from the endpoint already.
+        let unfiltered = LookupFilters::default();
+        match backend::resolve(&headwords, word, &unfiltered, self.config.root_depth) {
+            Err(OxdError::NotFound(_)) => Err(self.config.nothing_found(word)),
+            lookup => lookup,
+        }
+    }
+
+    /// Fetches the entry of a headword or root form.
+    ///
+    /// With a `memo`, a headword is fetched once however many threads ask for it;
+    /// the others wait for its entry. Failures other than a missing entry are not remembered.
+    fn headword_entry(
+        &self,
+        headword: &str,
+        filters: &LookupFilters,
+        memo: Option<&EntryMemo>,
+    ) -> Result<RetrieveEntry, OxdError> {
+        let Some(memo) = memo else {
+            return self.entry_with(headword, filters);
+        };
+        let slot = Arc::clone(memo.lock().unwrap().entry(headword.to_owned()).or_default());
+        let mut slot = slot.lock().unwrap();
+        if slot.is_none() {
+            *slot = Some(match self.entry_with(headword, filters) {
+                Ok(entry) => Some(entry),
+                Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => None,
+                Err(err) => return Err(err),
+            });
+        }
+        slot.clone()
+            .flatten()
+            .ok_or_else(|| OxdError::NotFound(headword.to_owned()))
+    }
+
+    /// Answers a request from what the client remembers, the [ResponseCache]
+    /// or else the API, and decodes the response.
+    fn get<T: DeserializeOwned>(&self, request: Request) -> Result<T, OxdError> {
+        if let Some(answer) = self.config.remembered(&request) {
+            return answer;
+        }
+        if let Some(cached) = self.config.cached(&request.key) {
+            return Ok(cached);
+        }
+        let body = self.fetch(&request)?;
+        self.config.answer(&request, body)
+    }
+
+    /// Sends a GET request with credentials and returns the response body,
+    /// retrying as the [RetryPolicy] allows.
+    fn fetch(&self, request: &Request) -> Result<String, OxdError> {
+        self.config.check_online(&request.word)?;
+        let mut attempt = 1;
+        loop {
+            match self.send(request) {
+                Err(err) => match self
+                    .config
+                    .retry
+                    .retry_delay(request.url.as_str(), attempt, &err)
+                {
+                    Some(delay) => thread::sleep(delay),
+                    None => return Err(err),
+                },
+                body => return body,
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Sends a single request, once the rate limiter lets it through.
+    fn send(&self, request: &Request) -> Result<String, OxdError> {
+        if let Some(rate_limiter) = &self.config.rate_limiter {
+            thread::sleep(rate_limiter.acquire()?);
+        }
+        let res = self
+            .http
+            .get(request.url.clone())
+            .headers(self.config.credentials.clone())
+            .send()?;
+        if let Some(err) = OxdError::from_status(res.status(), res.headers(), &request.word) {
+            return Err(err);
+        }
+        Ok(res.text()?)
+    }
+}
+
+/// The entries of one lookup, or of many sharing a memo, for [backend::resolve].
+#[cfg(feature = "blocking")]
+struct Headwords<'a> {
+    client: &'a OxdClient,
+    filters: &'a LookupFilters,
+    memo: Option<&'a EntryMemo>,
+}
+
+#[cfg(feature = "blocking")]
+impl DictionaryBackend for Headwords<'_> {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        self.client.headword_entry(word, self.filters, self.memo)
+    }
+
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        self.client.lemmas(word)
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        self.client.search(query, limit)
+    }
+}
+
+#[cfg(feature = "blocking")]
+impl DictionaryBackend for OxdClient {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        OxdClient::entry(self, word)
+    }
+
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        OxdClient::lemmas(self, word)
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        OxdClient::search(self, query, limit)
+    }
+
+    fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        OxdClient::audio(self, url)
+    }
+
+    /// Filters on the server, following roots as deep as the client is configured to.
+    fn lookup_with(&self, word: &str, filters: &LookupFilters) -> Result<Lookup, OxdError> {
+        self.entries_with(word, filters)
+    }
+}
@@ -0,0 +1,929 @@
+/*! A [DictionaryBackend] asking a DICT server, as described by RFC 2229
+ *
+ * [DictClient] opens a connection per request, introduces itself with `CLIENT`,
+ * sends one of `SHOW DB`, `SHOW STRAT`, `DEFINE` or `MATCH` and reads the reply.
+ *
+ * Each definition becomes a [LexicalEntry] whose lexical category is the database
+ * it came from, e.g. `wn`, and whose senses are the paragraphs of its text.
+ * Searches are `MATCH` requests, so suggestions for unknown words follow
+ * whichever strategy the client is set to, e.g. `lev` for near spellings.
+ *
+ * [DictServer] works the other way round: it answers DICT clients
+ * with the entries of any backend, e.g. an [OxdClient](crate::OxdClient) and its cache.
+ */
+
+use crate::backend::DictionaryBackend;
+use crate::display::Display;
+use crate::error::OxdError;
+use crate::lookup::word_id;
+use crate::models::lemmas::Lemmatron;
+use crate::models::search::{SearchResult, SearchResults};
+use crate::models::{Entry, HeadwordEntry, LexicalCategory, LexicalEntry, RetrieveEntry, Sense};
+use reqwest::Url;
+use serde_json::json;
+use std::collections::HashSet;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
+use std::{process, thread};
+
+/// A client of a DICT server such as dictd.
+///
+/// ```no_run
+/// use oxd::dict::DictClient;
+/// use oxd::DictionaryBackend;
+///
+/// let client = DictClient::from_url("dict://dict.org/wn").unwrap();
+/// let entry = client.entry("rust").unwrap();
+/// let suggestions = client.strategy("lev").search("rsut", 5).unwrap();
+/// ```
+#[derive(Debug, Clone)]
+pub struct DictClient {
+    host: String,
+    port: u16,
+    database: String,
+    strategy: String,
+    timeout: Duration,
+}
+
+/// A database or strategy a server offers, with its description.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listing {
+    pub name: String,
+    pub description: String,
+}
+
+/// The text a database has for a word.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub word: String,
+    pub database: String,
+    pub description: String,
+    pub text: String,
+}
+
+/// A word a database has that matches a query.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Match {
+    pub database: String,
+    pub word: String,
+}
+
+impl DictClient {
+    /// The port DICT servers listen on.
+    pub const DEFAULT_PORT: u16 = 2628;
+
+    /// A client of the server at `host`, searching all its databases
+    /// with the server's default strategy.
+    pub fn new(host: impl Into<String>) -> Self {
+        DictClient {
+            host: host.into(),
+            port: DictClient::DEFAULT_PORT,
+            database: "*".to_owned(),
+            strategy: ".".to_owned(),
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    /// A client for a URL such as `dict://dict.org:2628/wn`.
+    /// The database in the path is optional.
+    pub fn from_url(url: &str) -> Result<Self, OxdError> {
+        let invalid = || {
+            OxdError::InvalidConfig(format!(
+                "expected dict://HOST[:PORT][/DATABASE], not \"{url}\""
+            ))
+        };
+        let parsed = Url::parse(url).map_err(|_| invalid())?;
+        let host = match parsed.host_str() {
+            // IPv6 addresses come bracketed, as in dict://[::1]:2628.
+            Some(host) if parsed.scheme() == "dict" && !host.is_empty() => {
+                host.trim_start_matches('[').trim_end_matches(']')
+            }
+            _ => return Err(invalid()),
+        };
+        let port = parsed.port().unwrap_or(DictClient::DEFAULT_PORT);
+        let database = parsed.path().trim_start_matches('/');
+        let client = DictClient::new(host).port(port);
+        Ok(match database.trim_end_matches('/') {
+            "" => client,
+            database => client.database(database),
+        })
+    }
+
+    pub fn port(mut self, port: u16) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// The database to search: a name from [databases](Self::databases),
+    /// `*` for all of them (the default) or `!` for the first one with a match.
+    pub fn database(mut self, database: impl Into<String>) -> Self {
+        self.database = database.into();
+        self
+    }
+
+    /// The strategy of searches: a name from [strategies](Self::strategies),
+    /// or `.` for the server's default.
+    pub fn strategy(mut self, strategy: impl Into<String>) -> Self {
+        self.strategy = strategy.into();
+        self
+    }
+
+    /// How long to wait for the server to connect or reply, 10 seconds by default.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// The databases of the server, from `SHOW DB`.
+    pub fn databases(&self) -> Result<Vec<Listing>, OxdError> {
+        self.listings("SHOW DB", 110, 554)
+    }
+
+    /// The search strategies of the server, from `SHOW STRAT`.
+    pub fn strategies(&self) -> Result<Vec<Listing>, OxdError> {
+        self.listings("SHOW STRAT", 111, 555)
+    }
+
+    /// The definitions of `word` in the client's database, from `DEFINE`.
+    pub fn define(&self, word: &str) -> Result<Vec<Definition>, OxdError> {
+        let mut connection = self.connect()?;
+        let command = format!("DEFINE {} {}", self.database, quote(word)?);
+        let (code, line) = connection.command(&command)?;
+        let definitions = match code {
+            150 => {
+                let mut definitions = vec![];
+                loop {
+                    let (code, line) = connection.status()?;
+                    match code {
+                        151 => {
+                            let atoms = atoms(&line);
+                            let text = connection.text()?;
+                            definitions.push(Definition {
+                                word: atoms.get(1).cloned().unwrap_or_else(|| word.to_owned()),
+                                database: atoms.get(2).cloned().unwrap_or_default(),
+                                description: atoms.get(3).cloned().unwrap_or_default(),
+                                text,
+                            });
+                        }
+                        250 => break definitions,
+                        _ => return Err(connection.unexpected(&line)),
+                    }
+                }
+            }
+            552 => {
+                connection.quit();
+                return Err(OxdError::NotFound(word.to_owned()));
+            }
+            _ => return Err(self.refused(&connection, code, &line, None)),
+        };
+        connection.quit();
+        Ok(definitions)
+    }
+
+    /// The words matching `word` with `strategy` in the client's database, from `MATCH`.
+    pub fn matches(&self, word: &str, strategy: &str) -> Result<Vec<Match>, OxdError> {
+        let mut connection = self.connect()?;
+        let command = format!("MATCH {} {strategy} {}", self.database, quote(word)?);
+        let (code, line) = connection.command(&command)?;
+        let matches = match code {
+            152 => {
+                let matches = connection
+                    .text()?
+                    .lines()
+                    .filter_map(|line| match &atoms(line)[..] {
+                        [database, word, ..] => Some(Match {
+                            database: database.clone(),
+                            word: word.clone(),
+                        }),
+                        _ => None,
+                    })
+                    .collect();
+                connection.expect(250)?;
+                matches
+            }
+            552 => vec![],
+            _ => return Err(self.refused(&connection, code, &line, Some(strategy))),
+        };
+        connection.quit();
+        Ok(matches)
+    }
+
+    /// Matches of `query` with `strategy` as suggestions,
+    /// labelled with the database they were found in.
+    pub fn suggestions(
+        &self,
+        query: &str,
+        strategy: &str,
+        limit: usize,
+    ) -> Result<SearchResults, OxdError> {
+        let mut results: Vec<SearchResult> = vec![];
+        for found in self.matches(query, strategy)? {
+            if results.iter().any(|result| result.word == found.word) {
+                continue;
+            }
+            results.push(SearchResult {
+                id: word_id(&found.word),
+                word: found.word,
+                label: Some(found.database),
+                match_string: Some(query.to_owned()),
+                match_type: Some(strategy.to_owned()),
+                region: None,
+                score: None,
+            });
+        }
+        results.truncate(limit);
+        Ok(SearchResults {
+            metadata: self.metadata(),
+            results,
+        })
+    }
+
+    fn listings(&self, command: &str, listed: u16, none: u16) -> Result<Vec<Listing>, OxdError> {
+        let mut connection = self.connect()?;
+        let (code, line) = connection.command(command)?;
+        let listings = if code == listed {
+            let listings = connection
+                .text()?
+                .lines()
+                .filter_map(|line| match &atoms(line)[..] {
+                    [name, description, ..] => Some(Listing {
+                        name: name.clone(),
+                        description: description.clone(),
+                    }),
+                    [name] => Some(Listing {
+                        name: name.clone(),
+                        description: String::new(),
+                    }),
+                    [] => None,
+                })
+                .collect();
+            connection.expect(250)?;
+            listings
+        } else if code == none {
+            vec![]
+        } else {
+            return Err(connection.unexpected(&line));
+        };
+        connection.quit();
+        Ok(listings)
+    }
+
+    /// Connects, reads the banner and introduces oxd with `CLIENT`.
+    fn connect(&self) -> Result<Connection, OxdError> {
+        let url = match self.host.contains(':') {
+            true => format!("dict://[{}]:{}", self.host, self.port),
+            false => format!("dict://{}:{}", self.host, self.port),
+        };
+        let fail = |source: io::Error| OxdError::Dict {
+            url: url.clone(),
+            source,
+        };
+        let mut last_error = io::Error::new(io::ErrorKind::NotFound, "no address found");
+        let mut stream = None;
+        for addr in (self.host.as_str(), self.port)
+            .to_socket_addrs()
+            .map_err(fail)?
+        {
+            match TcpStream::connect_timeout(&addr, self.timeout) {
+                Ok(connected) => {
+                    stream = Some(connected);
+                    break;
+                }
+                Err(err) => last_error = err,
+            }
+        }
+        let stream = stream.ok_or_else(|| fail(last_error))?;
+        stream
+            .set_read_timeout(Some(self.timeout))
+            .and_then(|_| stream.set_write_timeout(Some(self.timeout)))
+            .map_err(fail)?;
+        let reader = BufReader::new(stream.try_clone().map_err(fail)?);
+        let mut connection = Connection {
+            url,
+            reader,
+            writer: stream,
+        };
+        connection.expect(220)?;
+        let (code, line) = connection.command(concat!("CLIENT oxd ", env!("CARGO_PKG_VERSION")))?;
+        if code != 250 {
+            return Err(connection.unexpected(&line));
+        }
+        Ok(connection)
+    }
+
+    /// Explains why the server refused a `DEFINE`, or a `MATCH` with `strategy`.
+    fn refused(
+        &self,
+        connection: &Connection,
+        code: u16,
+        line: &str,
+        strategy: Option<&str>,
+    ) -> OxdError {
+        match (code, strategy) {
+            (550, _) => OxdError::InvalidConfig(format!(
+                "{} has no database \"{}\"",
+                connection.url, self.database
+            )),
+            (551, Some(strategy)) => OxdError::InvalidConfig(format!(
+                "{} has no search strategy \"{strategy}\"",
+                connection.url
+            )),
+            _ => connection.unexpected(line),
+        }
+    }
+
+    fn metadata(&self) -> serde_json::Value {
+        json!({"provider": format!("dict://{}:{}", self.host, self.port)})
+    }
+}
+
+/// An open connection to a DICT server.
+struct Connection {
+    url: String,
+    reader: BufReader<TcpStream>,
+    writer: TcpStream,
+}
+
+impl Connection {
+    fn fail(&self, source: io::Error) -> OxdError {
+        OxdError::Dict {
+            url: self.url.clone(),
+            source,
+        }
+    }
+
+    fn unexpected(&self, line: &str) -> OxdError {
+        self.fail(io::Error::other(format!("unexpected reply \"{line}\"")))
+    }
+
+    fn line(&mut self) -> Result<String, OxdError> {
+        let mut line = String::new();
+        match self.reader.read_line(&mut line) {
+            Ok(0) => Err(self.fail(io::ErrorKind::UnexpectedEof.into())),
+            Ok(_) => Ok(line.trim_end_matches(['\r', '\n']).to_owned()),
+            Err(err) => Err(self.fail(err)),
+        }
+    }
+
+    /// Reads a status line, e.g. `250 ok`, as its code and the whole line.
+    fn status(&mut self) -> Result<(u16, String), OxdError> {
+        let line = self.line()?;
+        match line.get(..3).and_then(|code| code.parse().ok()) {
+            Some(code) => Ok((code, line)),
+            None => Err(self.unexpected(&line)),
+        }
+    }
+
+    fn expect(&mut self, expected: u16) -> Result<(), OxdError> {
+        let (code, line) = self.status()?;
+        if code == expected {
+            Ok(())
+        } else {
+            Err(self.unexpected(&line))
+        }
+    }
+
+    fn command(&mut self, command: &str) -> Result<(u16, String), OxdError> {
+        let sent = self.writer.write_all(format!("{command}\r\n").as_bytes());
+        sent.map_err(|err| self.fail(err))?;
+        self.status()
+    }
+
+    /// Reads a text response up to its lone `.`, undoing the doubling of leading dots.
+    fn text(&mut self) -> Result<String, OxdError> {
+        let mut text = String::new();
+        loop {
+            let line = self.line()?;
+            if line == "." {
+                return Ok(text);
+            }
+            text.push_str(line.strip_prefix('.').unwrap_or(&line));
+            text.push('\n');
+        }
+    }
+
+    /// Says goodbye; the request has its answer whatever the server replies.
+    fn quit(mut self) {
+        let _ = self.command("QUIT");
+    }
+}
+
+/// Quotes a word for a command if it has spaces or quotes in it.
+fn quote(word: &str) -> Result<String, OxdError> {
+    let word = word.trim();
+    if word.is_empty() || word.chars().any(char::is_control) {
+        return Err(OxdError::NotFound(word.to_owned()));
+    }
+    if !word.contains([' ', '"', '\'', '\\']) {
+        return Ok(word.to_owned());
+    }
+    Ok(quoted(word))
+}
+
+/// Quotes a string, escaping backslashes and quotes.
+fn quoted(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Splits a line into words and quoted strings, e.g. `151 "rust" wn "WordNet"`.
+fn atoms(line: &str) -> Vec<String> {
+    let mut atoms = vec![];
+    let mut chars = line.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+            continue;
+        }
+        let mut atom = String::new();
+        if c == '"' || c == '\'' {
+            chars.next();
+            while let Some(next) = chars.next() {
+                match next {
+                    '\\' => atom.extend(chars.next()),
+                    next if next == c => break,
+                    next => atom.push(next),
+                }
+            }
+        } else {
+            while let Some(&next) = chars.peek() {
+                if next.is_whitespace() {
+                    break;
+                }
+                atom.push(next);
+                chars.next();
+            }
+        }
+        atoms.push(atom);
+    }
+    atoms
+}
+
+/// Splits a definition into senses: paragraphs are separated by blank lines,
+/// or start where a line is indented less than the one before, as in
+/// `n 1: a red oxide` followed by an indented continuation and then `2: a disease`.
+/// A first line repeating the headword is left out.
+fn senses(word: &str, text: &str) -> Vec<Sense> {
+    let mut paragraphs: Vec<String> = vec![];
+    let mut previous_indent = None;
+    for (i, line) in text.lines().enumerate() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            previous_indent = None;
+            continue;
+        }
+        if i == 0 && trimmed.eq_ignore_ascii_case(word) {
+            continue;
+        }
+        let indent = line.len() - line.trim_start().len();
+        match (paragraphs.last_mut(), previous_indent) {
+            (Some(paragraph), Some(previous)) if indent >= previous => {
+                paragraph.push(' ');
+                paragraph.push_str(trimmed);
+            }
+            _ => paragraphs.push(trimmed.to_owned()),
+        }
+        previous_indent = Some(indent);
+    }
+    paragraphs
+        .into_iter()
+        .map(|paragraph| Sense {
+            id: None,
+            definitions: Some(vec![paragraph]),
+            examples: None,
+            subsenses: None,
+            domains: None,
+            cross_reference_markers: None,
+            registers: None,
+            regions: None,
+            notes: None,
+            translations: None,
+            sentences: None,
+        })
+        .collect()
+}
+
+impl DictionaryBackend for DictClient {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        let definitions = self.define(word)?;
+        let mut headword_entries: Vec<HeadwordEntry> = vec![];
+        for definition in definitions {
+            let lexical_entry = LexicalEntry {
+                entries: vec![Entry {
+                    senses: Some(senses(&definition.word, &definition.text)),
+                    pronunciations: None,
+                    variant_forms: None,
+                    origins: None,
+                    grammatical_features: None,
+                    notes: None,
+                }],
+                language: String::new(),
+                lexical_category: LexicalCategory {
+                    id: definition.database,
+                    text: definition.description,
+                },
+                derivative_of: None,
+                grammatical_features: None,
+                text: definition.word.clone(),
+            };
+            match headword_entries
+                .iter_mut()
+                .find(|headword| headword.word == definition.word)
+            {
+                Some(headword) => headword.lexical_entries.push(lexical_entry),
+                None => headword_entries.push(HeadwordEntry {
+                    id: word_id(&definition.word),
+                    language: String::new(),
+                    lexical_entries: vec![lexical_entry],
+                    type_: "headword".to_owned(),
+                    word: definition.word,
+                }),
+            }
+        }
+        Ok(RetrieveEntry {
+            id: Some(word_id(word)),
+            metadata: self.metadata(),
+            headword_entries,
+            word: Some(word.to_owned()),
+        })
+    }
+
+    /// DICT has no notion of inflections, so every word is its own headword.
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        Err(OxdError::NotFound(word.to_owned()))
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        self.suggestions(query, &self.strategy, limit)
+    }
+}
+
+/// The strategies [DictServer] answers `MATCH` with; `.` stands for `lev`.
+const STRATEGIES: [(&str, &str); 3] = [
+    ("exact", "Match headwords exactly"),
+    ("prefix", "Match prefixes"),
+    ("lev", "Match headwords spelled alike"),
+];
+
+/// How many words a `MATCH` reply lists at most.
+const MATCH_LIMIT: usize = 50;
+
+/// How long a connection may sit idle before the server hangs up.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(600);
+
+/// The longest command line [DictServer] reads, in bytes with the line break, as RFC 2229 allows.
+const MAX_LINE: u64 = 1024;
+
+/// Serves the entries of a [DictionaryBackend] as one DICT database,
+/// so that dict-aware tools such as the `dict` command or GoldenDict can look words up.
+///
+/// `DEFINE` gives one definition per entry a [lookup](DictionaryBackend::lookup) finds,
+/// rendered by [Display](crate::display::Display) as plain text, without colors.
+///
+/// ```no_run
+/// use oxd::backend::MemoryBackend;
+/// use oxd::dict::DictServer;
+/// use std::net::TcpListener;
+///
+/// let backend = MemoryBackend::load_dir("responses").unwrap();
+/// let listener = TcpListener::bind(("127.0.0.1", 2628)).unwrap();
+/// DictServer::new(&backend, "Saved responses").serve(listener);
+/// ```
+pub struct DictServer<'a> {
+    backend: &'a (dyn DictionaryBackend + Sync),
+    database: Listing,
+    max_connections: usize,
+}
+
+impl<'a> DictServer<'a> {
+    /// A server offering the entries of `backend` as the database `oxd`.
+    pub fn new(
+        backend: &'a (dyn DictionaryBackend + Sync),
+        description: impl Into<String>,
+    ) -> Self {
+        DictServer {
+            backend,
+            database: Listing {
+                name: "oxd".to_owned(),
+                description: description.into(),
+            },
+            max_connections: 32,
+        }
+    }
+
+    /// How many clients may be connected at once, 32 by default.
+    /// Others are turned away until one of them leaves.
+    pub fn max_connections(mut self, max_connections: usize) -> Self {
+        self.max_connections = max_connections;
+        self
+    }
+
+    /// Answers connections, each on a thread of its own, for as long as the process runs.
+    /// A connection that fails only ends that conversation.
+    pub fn serve(&self, listener: TcpListener) {
+        let connections = AtomicUsize::new(0);
+        let active = &AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for mut stream in listener.incoming().flatten() {
+                if active.load(Ordering::Acquire) >= self.max_connections {
+                    let _ = stream
+                        .write_all(b"420 server temporarily unavailable: too many connections\r\n");
+                    continue;
+                }
+                active.fetch_add(1, Ordering::AcqRel);
+                let id = connections.fetch_add(1, Ordering::Relaxed);
+                scope.spawn(move || {
+                    let _ = self.converse(stream, id);
+                    active.fetch_sub(1, Ordering::AcqRel);
+                });
+            }
+        });
+    }
+
+    /// Reads commands from a client and replies until it quits or hangs up.
+    fn converse(&self, stream: TcpStream, id: usize) -> io::Result<()> {
+        stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+        stream.set_write_timeout(Some(IDLE_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut writer = stream;
+        let mut reply = |text: &str| writer.write_all(text.replace('\n', "\r\n").as_bytes());
+        reply(&format!(
+            "220 oxd {} <> <{}.{id}@oxd>\n",
+            env!("CARGO_PKG_VERSION"),
+            process::id()
+        ))?;
+        let mut line = vec![];
+        loop {
+            line.clear();
+            let read = reader
+                .by_ref()
+                .take(MAX_LINE)
+                .read_until(b'\n', &mut line)?;
+            if read == 0 {
+                return Ok(());
+            }
+            if read as u64 == MAX_LINE && !line.ends_with(b"\n") {
+                reader.skip_until(b'\n')?;
+                reply("500 line too long\n")?;
+                continue;
+            }
+            let atoms = atoms(&String::from_utf8_lossy(&line));
+            let Some((command, parameters)) = atoms.split_first() else {
+                continue;
+            };
+            let parameters: Vec<&str> = parameters.iter().map(String::as_str).collect();
+            if command.eq_ignore_ascii_case("QUIT") {
+                return reply("221 bye\n");
+            }
+            reply(&self.answer(&command.to_ascii_uppercase(), &parameters))?;
+        }
+    }
+
+    /// The reply to a command other than `QUIT`.
+    fn answer(&self, command: &str, parameters: &[&str]) -> String {
+        match (command, parameters) {
+            ("CLIENT", _) => "250 ok\n".to_owned(),
+            ("STATUS", _) => "210 up\n".to_owned(),
+            ("HELP", _) => format!("113 help text follows\n{}250 ok\n", text_block(HELP)),
+            ("SHOW", [what, rest @ ..]) => match (what.to_ascii_uppercase().as_str(), rest) {
+                ("DB" | "DATABASES", []) => format!(
+                    "110 1 databases present\n{}250 ok\n",
+                    text_block(&format!(
+                        "{} {}",
+                        self.database.name,
+                        quoted(&self.database.description)
+                    ))
+                ),
+                ("STRAT" | "STRATEGIES", []) => {
+                    let listing: Vec<String> = STRATEGIES
+                        .iter()
+                        .map(|(name, description)| format!("{name} {}", quoted(description)))
+                        .collect();
+                    format!(
+                        "111 {} strategies present\n{}250 ok\n",
+                        STRATEGIES.len(),
+                        text_block(&listing.join("\n"))
+                    )
+                }
+                ("INFO", [database]) if self.serves(database) => format!(
+                    "112 database information follows\n{}250 ok\n",
+                    text_block(&self.database.description)
+                ),
+                ("INFO", [_]) => "550 invalid database, use SHOW DB for a list\n".to_owned(),
+                ("SERVER", []) => format!(
+                    "114 server information follows\n{}250 ok\n",
+                    text_block(&format!("oxd {}", env!("CARGO_PKG_VERSION")))
+                ),
+                _ => "501 syntax error, illegal parameters\n".to_owned(),
+            },
+            ("DEFINE", [database, word]) if self.serves(database) => self.define(word),
+            ("MATCH", [database, strategy, word]) if self.serves(database) => {
+                self.find(strategy, word)
+            }
+            ("DEFINE", [_, _]) | ("MATCH", [_, _, _]) => {
+                "550 invalid database, use SHOW DB for a list\n".to_owned()
+            }
+            ("DEFINE" | "MATCH" | "SHOW", _) => "501 syntax error, illegal parameters\n".to_owned(),
+            ("OPTION" | "AUTH" | "SASLAUTH" | "SASLRESP", _) => {
+                "502 command not implemented\n".to_owned()
+            }
+            _ => "500 unknown command\n".to_owned(),
+        }
+    }
+
+    /// Whether a command naming `database` asks this server's database.
+    fn serves(&self, database: &str) -> bool {
+        matches!(database, "*" | "!") || database == self.database.name
+    }
+
+    /// The `DEFINE` reply: each entry of the lookup, with how the word inflects above the first.
+    fn define(&self, word: &str) -> String {
+        let lookup = match self.backend.lookup(word) {
+            Ok(lookup) => lookup,
+            Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => {
+                return "552 no match\n".to_owned()
+            }
+            Err(err) => return unavailable(&err),
+        };
+        let mut canvas = String::new();
+        lookup.lemmas.display(&mut canvas);
+        let entries = lookup.into_display_order();
+        let mut reply = format!("150 {} definitions retrieved\n", entries.len());
+        for retrieve_entry in entries {
+            retrieve_entry.display(&mut canvas);
+            let headword = retrieve_entry
+                .headword_entries
+                .first()
+                .map_or(word, |headword| headword.word.as_str());
+            reply.push_str(&format!(
+                "151 {} {} {}\n{}",
+                quoted(headword),
+                self.database.name,
+                quoted(&self.database.description),
+                text_block(&strip_ansi(&canvas))
+            ));
+            canvas.clear();
+        }
+        reply + "250 ok\n"
+    }
+
+    /// The `MATCH` reply: the entry of the word itself for `exact`,
+    /// otherwise the backend's search results, only those starting with the word for `prefix`.
+    fn find(&self, strategy: &str, word: &str) -> String {
+        let found = match strategy {
+            "exact" => self.backend.entry(word).map(|entry| {
+                entry
+                    .headword_entries
+                    .into_iter()
+                    .map(|headword| headword.word)
+                    .collect()
+            }),
+            "prefix" | "lev" | "." => self.backend.search(word, MATCH_LIMIT).map(|results| {
+                let prefix = word.to_lowercase();
+                results
+                    .ranked()
+                    .into_iter()
+                    .map(|result| result.word.clone())
+                    .filter(|found| {
+                        strategy != "prefix" || found.to_lowercase().starts_with(&prefix)
+                    })
+                    .collect()
+            }),
+            _ => return "551 invalid strategy, use SHOW STRAT for a list\n".to_owned(),
+        };
+        let mut words: Vec<String> = match found {
+            Ok(words) => words,
+            Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => vec![],
+            Err(err) => return unavailable(&err),
+        };
+        let mut seen = HashSet::new();
+        words.retain(|word| seen.insert(word.clone()));
+        if words.is_empty() {
+            return "552 no match\n".to_owned();
+        }
+        let listing: Vec<String> = words
+            .iter()
+            .map(|word| format!("{} {}", self.database.name, quoted(word)))
+            .collect();
+        format!(
+            "152 {} matches found\n{}250 ok\n",
+            words.len(),
+            text_block(&listing.join("\n"))
+        )
+    }
+}
+
+/// What `HELP` lists.
+const HELP: &str = "DEFINE database word
+MATCH database strategy word
+SHOW DB
+SHOW STRAT
+SHOW INFO database
+SHOW SERVER
+CLIENT info
+STATUS
+HELP
+QUIT";
+
+/// The reply when the backend fails for a reason other than a missing word,
+/// e.g. the API quota running out.
+fn unavailable(err: &OxdError) -> String {
+    let reason = err.to_string();
+    format!(
+        "420 server temporarily unavailable: {}\n",
+        reason.lines().next().unwrap_or_default()
+    )
+}
+
+/// Leaves out the escape sequences [Display](crate::display::Display) colors text with,
+/// e.g. `\x1b[1m`, whether or not colors are on.
+fn strip_ansi(text: &str) -> String {
+    let mut plain = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            plain.push(c);
+        } else if chars.next() == Some('[') {
+            // Parameters and intermediates run up to a final byte in @ to ~.
+            for next in chars.by_ref() {
+                if ('@'..='~').contains(&next) {
+                    break;
+                }
+            }
+        }
+    }
+    plain
+}
+
+/// Frames text sent after a reply: lines starting with a dot get another one,
+/// and a line with a single dot ends it. Trailing whitespace is left out.
+fn text_block(text: &str) -> String {
+    let mut block = String::new();
+    for line in text.trim_end().lines() {
+        let line = line.trim_end();
+        if line.starts_with('.') {
+            block.push('.');
+        }
+        block.push_str(line);
+        block.push('\n');
+    }
+    block + ".\n"
+}
+
+#[test]
+fn test_from_url() {
+    let client = DictClient::from_url("dict://dict.org/wn").unwrap();
+    assert_eq!((client.host.as_str(), client.port), ("dict.org", 2628));
+    assert_eq!(client.database, "wn");
+    let client = DictClient::from_url("dict://[::1]:2629").unwrap();
+    assert_eq!((client.host.as_str(), client.port), ("::1", 2629));
+    assert_eq!(client.database, "*");
+    let client = DictClient::from_url("dict://[::1]/").unwrap();
+    assert_eq!((client.host.as_str(), client.port), ("::1", 2628));
+    for url in [
+        "dict://",
+        "dict:///wn",
+        "http://dict.org",
+        "dict://dict.org:port",
+    ] {
+        assert!(DictClient::from_url(url).is_err(), "{url}");
+    }
+}
+
+#[test]
+fn test_strip_ansi() {
+    assert_eq!(
+        strip_ansi("\x1b[1;32mrust\x1b[0m \x1b[2m/rʌst/\x1b[0m"),
+        "rust /rʌst/"
+    );
+    assert_eq!(text_block("rust\n.rs files  \n\n"), "rust\n..rs files\n.\n");
+}
+
+#[test]
+fn test_atoms() {
+    assert_eq!(
+        atoms(r#"151 "ice cream" wn "WordNet (r) 3.0 \"2006\"""#),
+        ["151", "ice cream", "wn", "WordNet (r) 3.0 \"2006\""]
+    );
+    assert_eq!(quote("ice \"cream\"").unwrap(), r#""ice \"cream\"""#);
+    assert_eq!(quote("rust").unwrap(), "rust");
+}
+
+#[test]
+fn test_senses() {
+    let text = "rust\n    n 1: a red or brown oxide coating on iron\n         in the presence of moisture\n    2: a plant disease\n\n    v 1: become coated with oxide\n";
+    let senses = senses("rust", text);
+    let definitions: Vec<_> = senses
+        .iter()
+        .map(|sense| sense.definitions.as_ref().unwrap()[0].as_str())
+        .collect();
+    assert_eq!(
+        definitions,
+        [
+            "n 1: a red or brown oxide coating on iron in the presence of moisture",
+            "2: a plant disease",
+            "v 1: become coated with oxide"
+        ]
+    );
+}
@@ -0,0 +1,30 @@
+/*! Where oxd keeps files between runs, following the XDG base directories */
+
+use crate::error::OxdError;
+use std::env;
+use std::path::PathBuf;
+
+/// The directory for state such as the monthly request count,
+/// `$XDG_STATE_HOME/oxd` or `~/.local/state/oxd`.
+pub(crate) fn state_dir() -> Result<PathBuf, OxdError> {
+    xdg_dir("XDG_STATE_HOME", ".local/state")
+}
+
+/// The directory for cached responses, `$XDG_CACHE_HOME/oxd` or `~/.cache/oxd`.
+pub(crate) fn cache_dir() -> Result<PathBuf, OxdError> {
+    xdg_dir("XDG_CACHE_HOME", ".cache")
+}
+
+/// The directory for imported dictionaries, `$XDG_DATA_HOME/oxd` or `~/.local/share/oxd`.
+pub(crate) fn data_dir() -> Result<PathBuf, OxdError> {
+    xdg_dir("XDG_DATA_HOME", ".local/share")
+}
+
+fn xdg_dir(var: &str, fallback: &str) -> Result<PathBuf, OxdError> {
+    let base = env::var_os(var)
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(fallback)))
+        .ok_or_else(|| OxdError::InvalidConfig(format!("neither {var} nor HOME is set")))?;
+    Ok(base.join("oxd"))
+}
@@ -1,5 +1,10 @@
 /*! Display entries as terminal text or html */
 
+use crate::models::catalog::*;
+use crate::models::lemmas::*;
+use crate::models::search::*;
+use crate::models::sentences::*;
+use crate::models::thesaurus::*;
 use crate::models::*;
 use colored::Colorize;
 use std::fmt::Write;
@@ -30,7 +35,7 @@ impl Display for RetrieveEntry {
 impl Display for HeadwordEntry {
     fn display(&self, output: &mut String) {
         write!(output, "{}  ", self.word.bold().underline()).unwrap();
-        if has_consistent_pronunciation(&self) {
+        if has_consistent_pronunciation(self) {
             // Assume "at least one `LexicalEntry`" and "must only one `Entry`"
             self.lexical_entries[0].entries[0]
                 .pronunciations
@@ -49,7 +54,7 @@ impl Display for HeadwordEntry {
     }
     fn to_html(&self, output: &mut String) {
         write!(output, "<p><u><b>{}</b></u>  ", self.word).unwrap();
-        if has_consistent_pronunciation(&self) {
+        if has_consistent_pronunciation(self) {
             // Assume "at least one `LexicalEntry`" and "must only one `Entry`"
             self.lexical_entries[0].entries[0]
                 .pronunciations
@@ -72,12 +77,7 @@ impl Display for LexicalEntry {
         if is_empty_entries(&self.entries) {
             return;
         }
-        write!(
-            output,
-            "\n{}  ",
-            self.lexical_category.id.italic().magenta()
-        )
-        .unwrap();
+        self.lexical_category.display(output);
         self.derivative_of.display(output);
         self.entries.display(output);
     }
@@ -85,7 +85,7 @@ impl Display for LexicalEntry {
         if is_empty_entries(&self.entries) {
             return;
         }
-        write!(output, "<p><i>{}</i>  ", self.lexical_category.id).unwrap();
+        self.lexical_category.to_html(output);
         self.entries.to_html(output);
     }
 }
@@ -98,6 +98,7 @@ impl Display for Entry {
                 self.pronunciations.display(output);
             }
         }
+        self.grammatical_features.display(output);
         self.variant_forms.display(output);
         writeln!(output).unwrap();
         self.senses.display(output);
@@ -109,6 +110,7 @@ impl Display for Entry {
                 self.pronunciations.display(output);
             }
         }
+        self.grammatical_features.to_html(output);
         self.variant_forms.to_html(output);
         // Assume "must only one `Entry` to close <p> tag"
         write!(output, "</p><ul>").unwrap();
@@ -125,38 +127,56 @@ impl Display for Sense {
         let mut c = String::new();
         self.domains.display(&mut c);
         self.registers.display(&mut c);
-        if self.domains.is_some() || self.registers.is_some() {
+        self.regions.display(&mut c);
+        self.notes.display(&mut c);
+        if !c.is_empty() {
             writeln!(&mut c).unwrap();
         }
         self.definitions.display(&mut c);
         self.cross_reference_markers.display(&mut c);
+        if let Some(translations) = &self.translations {
+            writeln!(&mut c, "{}", join_translations(translations, false)).unwrap();
+        }
         self.examples.display(&mut c);
+        self.sentences.display(&mut c);
         self.subsenses.display(&mut c);
-        c = c.replace("\n", "\n  ");
-        c.pop();
-        c.pop();
-        c.insert_str(0, "- ");
-        write!(output, "{c}").unwrap();
+        write_item(c, output);
     }
     fn to_html(&self, output: &mut String) {
         if is_empty_sense(self) {
             return;
         }
         write!(output, "<li>").unwrap();
-        self.domains.to_html(output);
-        self.registers.to_html(output);
-        if self.domains.is_some() || self.registers.is_some() {
-            write!(output, "<br>").unwrap();
+        let mut c = String::new();
+        self.domains.to_html(&mut c);
+        self.registers.to_html(&mut c);
+        self.regions.to_html(&mut c);
+        self.notes.to_html(&mut c);
+        if !c.is_empty() {
+            write!(output, "{c}<br>").unwrap();
         }
         self.definitions.to_html(output);
         self.cross_reference_markers.to_html(output);
+        if let Some(translations) = &self.translations {
+            write!(output, "{}<br>", join_translations(translations, true)).unwrap();
+        }
         self.examples.to_html(output);
+        self.sentences.to_html(output);
         write!(output, "<ul>").unwrap();
         self.subsenses.to_html(output);
         write!(output, "</ul></li>").unwrap();
     }
 }
 
+/// Writes lines as a list item, indenting all but the first.
+fn write_item(mut c: String, output: &mut String) {
+    c = c.replace('\n', "\n  ");
+    c.pop();
+    c.pop();
+    c.insert_str(0, "- ");
+    write!(output, "{c}").unwrap();
+}
+
 impl<T: Display> Display for Option<T> {
     fn display(&self, output: &mut String) {
         if let Some(value) = &self {
@@ -189,7 +209,7 @@ impl Display for String {
         if !self.ends_with('.') {
             write!(output, ".").unwrap();
         }
-        writeln!(output, "").unwrap();
+        writeln!(output).unwrap();
     }
     fn to_html(&self, output: &mut String) {
         write!(output, "{}", self).unwrap();
@@ -203,11 +223,75 @@ impl Display for String {
 impl Display for Example {
     fn display(&self, output: &mut String) {
         let text = format!("\"{}\"", self.text.trim());
-        writeln!(output, "{}", text.italic().blue()).unwrap();
+        write!(output, "{}", text.italic().blue()).unwrap();
+        if let Some(translations) = &self.translations {
+            let texts: Vec<&str> = translations.iter().map(|t| t.text.trim()).collect();
+            let text = format!("\"{}\"", texts.join("\", \""));
+            write!(output, " → {}", text.italic()).unwrap();
+        }
+        writeln!(output).unwrap();
     }
     fn to_html(&self, output: &mut String) {
         let text = format!("\"{}\"", self.text.trim());
-        write!(output, "{}<br>", text).unwrap();
+        write!(output, "{}", text).unwrap();
+        if let Some(translations) = &self.translations {
+            let texts: Vec<&str> = translations.iter().map(|t| t.text.trim()).collect();
+            write!(output, " → \"{}\"", texts.join("\", \"")).unwrap();
+        }
+        write!(output, "<br>").unwrap();
+    }
+}
+
+impl Display for Translation {
+    fn display(&self, output: &mut String) {
+        write!(output, "{}", self.text.bold()).unwrap();
+        let mut labels = String::new();
+        self.grammatical_features.display(&mut labels);
+        self.domains.display(&mut labels);
+        self.registers.display(&mut labels);
+        self.regions.display(&mut labels);
+        self.notes.display(&mut labels);
+        if !labels.is_empty() {
+            write!(output, " {}", labels.trim_end()).unwrap();
+        }
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<b>{}</b>", self.text).unwrap();
+        let mut labels = String::new();
+        self.grammatical_features.to_html(&mut labels);
+        self.domains.to_html(&mut labels);
+        self.registers.to_html(&mut labels);
+        self.regions.to_html(&mut labels);
+        self.notes.to_html(&mut labels);
+        if !labels.is_empty() {
+            write!(output, " {}", labels.trim_end()).unwrap();
+        }
+    }
+}
+
+/// Renders translations of a sense on a single line.
+fn join_translations(translations: &[Translation], html: bool) -> String {
+    let rendered: Vec<String> = translations
+        .iter()
+        .map(|translation| {
+            let mut c = String::new();
+            if html {
+                translation.to_html(&mut c);
+            } else {
+                translation.display(&mut c);
+            }
+            c
+        })
+        .collect();
+    format!("→ {}", rendered.join(", "))
+}
+
+impl Display for Note {
+    fn display(&self, output: &mut String) {
+        write!(output, "({}) ", self.text).unwrap();
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "({}) ", self.text).unwrap();
     }
 }
 
@@ -239,14 +323,14 @@ impl Display for DerivativeOf {
 
 impl Display for Pronunciation {
     fn display(&self, output: &mut String) {
-        if self.phonetic_notation == "IPA" {
+        if self.phonetic_notation.as_deref() == Some("IPA") {
             if let Some(spelling) = &self.phonetic_spelling {
                 write!(output, "/{}/ ", spelling).unwrap();
             }
         }
     }
     fn to_html(&self, output: &mut String) {
-        if self.phonetic_notation == "IPA" {
+        if self.phonetic_notation.as_deref() == Some("IPA") {
             if let Some(spelling) = &self.phonetic_spelling {
                 write!(output, "/{}/ ", spelling).unwrap();
             }
@@ -271,3 +355,306 @@ impl Display for Register {
         write!(output, "[{}] ", self.text).unwrap();
     }
 }
+
+impl Display for Region {
+    fn display(&self, output: &mut String) {
+        write!(output, "[{}] ", self.text).unwrap();
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "[{}] ", self.text).unwrap();
+    }
+}
+
+impl Display for GrammaticalFeature {
+    fn display(&self, output: &mut String) {
+        write!(output, "{} ", self.text.to_lowercase().italic()).unwrap();
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<i>{}</i> ", self.text.to_lowercase()).unwrap();
+    }
+}
+
+impl Display for LexicalCategory {
+    fn display(&self, output: &mut String) {
+        write!(output, "\n{}  ", self.id.italic().magenta()).unwrap();
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<p><i>{}</i>  ", self.id).unwrap();
+    }
+}
+
+impl Display for Thesaurus {
+    fn display(&self, output: &mut String) {
+        self.headword_entries.display(output);
+    }
+    fn to_html(&self, output: &mut String) {
+        self.headword_entries.to_html(output);
+    }
+}
+
+impl Display for ThesaurusHeadword {
+    fn display(&self, output: &mut String) {
+        writeln!(output, "{}", self.word.bold().underline()).unwrap();
+        self.lexical_entries.display(output);
+        writeln!(output).unwrap();
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<p><u><b>{}</b></u></p>", self.word).unwrap();
+        self.lexical_entries.to_html(output);
+    }
+}
+
+impl Display for ThesaurusLexicalEntry {
+    fn display(&self, output: &mut String) {
+        self.lexical_category.display(output);
+        writeln!(output).unwrap();
+        self.entries.display(output);
+    }
+    fn to_html(&self, output: &mut String) {
+        self.lexical_category.to_html(output);
+        write!(output, "</p><ul>").unwrap();
+        self.entries.to_html(output);
+        write!(output, "</ul>").unwrap();
+    }
+}
+
+impl Display for ThesaurusEntry {
+    fn display(&self, output: &mut String) {
+        self.senses.display(output);
+    }
+    fn to_html(&self, output: &mut String) {
+        self.senses.to_html(output);
+    }
+}
+
+impl Display for ThesaurusSense {
+    fn display(&self, output: &mut String) {
+        if is_empty_thesaurus_sense(self) {
+            return;
+        }
+        let mut c = String::new();
+        self.domains.display(&mut c);
+        self.registers.display(&mut c);
+        self.regions.display(&mut c);
+        if self.domains.is_some() || self.registers.is_some() || self.regions.is_some() {
+            writeln!(&mut c).unwrap();
+        }
+        if let Some(synonyms) = &self.synonyms {
+            writeln!(&mut c, "{}", join_words(synonyms)).unwrap();
+        }
+        if let Some(antonyms) = &self.antonyms {
+            writeln!(
+                &mut c,
+                "[{}]  {}",
+                "opposite".magenta(),
+                join_words(antonyms)
+            )
+            .unwrap();
+        }
+        self.examples.display(&mut c);
+        self.subsenses.display(&mut c);
+        write_item(c, output);
+    }
+    fn to_html(&self, output: &mut String) {
+        if is_empty_thesaurus_sense(self) {
+            return;
+        }
+        write!(output, "<li>").unwrap();
+        self.domains.to_html(output);
+        self.registers.to_html(output);
+        self.regions.to_html(output);
+        if self.domains.is_some() || self.registers.is_some() || self.regions.is_some() {
+            write!(output, "<br>").unwrap();
+        }
+        if let Some(synonyms) = &self.synonyms {
+            write!(output, "{}<br>", join_words(synonyms)).unwrap();
+        }
+        if let Some(antonyms) = &self.antonyms {
+            write!(output, "[opposite]  {}<br>", join_words(antonyms)).unwrap();
+        }
+        self.examples.to_html(output);
+        write!(output, "<ul>").unwrap();
+        self.subsenses.to_html(output);
+        write!(output, "</ul></li>").unwrap();
+    }
+}
+
+fn join_words(words: &[SynonymAntonym]) -> String {
+    let texts: Vec<&str> = words.iter().map(|word| word.text.as_str()).collect();
+    texts.join(", ")
+}
+
+impl Display for Lemmatron {
+    /// Writes a line such as "running → run (present participle)"
+    /// for every headword the word is an inflection of.
+    fn display(&self, output: &mut String) {
+        for line in inflection_lines(self) {
+            writeln!(output, "{}", line.dimmed()).unwrap();
+        }
+    }
+    fn to_html(&self, output: &mut String) {
+        for line in inflection_lines(self) {
+            write!(output, "<p>{}</p>", line).unwrap();
+        }
+    }
+}
+
+fn inflection_lines(lemmatron: &Lemmatron) -> Vec<String> {
+    let mut lines: Vec<String> = vec![];
+    for headword in &lemmatron.results {
+        for lexical_entry in &headword.lexical_entries {
+            for inflection_of in &lexical_entry.inflection_of {
+                if inflection_of.text == headword.word {
+                    continue;
+                }
+                let mut line = format!("{} → {}", headword.word, inflection_of.text);
+                if let Some(inflection) = lexical_entry.inflection() {
+                    write!(line, " ({inflection})").unwrap();
+                }
+                if !lines.contains(&line) {
+                    lines.push(line);
+                }
+            }
+        }
+    }
+    lines
+}
+
+impl Display for Sentence {
+    fn display(&self, output: &mut String) {
+        let text = format!("\"{}\"", self.text.trim());
+        write!(output, "{}", text.dimmed()).unwrap();
+        let mut labels = String::new();
+        self.regions.display(&mut labels);
+        self.registers.display(&mut labels);
+        self.domains.display(&mut labels);
+        if !labels.is_empty() {
+            write!(output, " {}", labels.trim_end().dimmed()).unwrap();
+        }
+        writeln!(output).unwrap();
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<small>\"{}\"", self.text.trim()).unwrap();
+        let mut labels = String::new();
+        self.regions.to_html(&mut labels);
+        self.registers.to_html(&mut labels);
+        self.domains.to_html(&mut labels);
+        if !labels.is_empty() {
+            write!(output, " {}", labels.trim_end()).unwrap();
+        }
+        write!(output, "</small><br>").unwrap();
+    }
+}
+
+impl Display for SearchResults {
+    /// Writes a numbered list, best match first.
+    fn display(&self, output: &mut String) {
+        for (rank, result) in self.ranked().into_iter().enumerate() {
+            write!(output, "{:>3}. {}", rank + 1, result.word.bold()).unwrap();
+            if let Some(score) = result.score {
+                write!(output, "  {}", format!("({score:.2})").dimmed()).unwrap();
+            }
+            writeln!(output).unwrap();
+        }
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<ol>").unwrap();
+        for result in self.ranked() {
+            write!(output, "<li>{}", result.word).unwrap();
+            if let Some(score) = result.score {
+                write!(output, " ({score:.2})").unwrap();
+            }
+            write!(output, "</li>").unwrap();
+        }
+        write!(output, "</ol>").unwrap();
+    }
+}
+
+impl Display for Labels {
+    /// Writes one label per line, its id followed by its name and type.
+    fn display(&self, output: &mut String) {
+        for label in &self.labels {
+            write!(output, "{}", label.id.bold()).unwrap();
+            if label.text != label.id {
+                write!(output, "  {}", label.text).unwrap();
+            }
+            if let Some(type_) = &label.type_ {
+                write!(output, "  {}", format!("({type_})").dimmed()).unwrap();
+            }
+            writeln!(output).unwrap();
+        }
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<dl>").unwrap();
+        for label in &self.labels {
+            write!(output, "<dt>{}</dt><dd>{}", label.id, label.text).unwrap();
+            if let Some(type_) = &label.type_ {
+                write!(output, " ({type_})").unwrap();
+            }
+            write!(output, "</dd>").unwrap();
+        }
+        write!(output, "</dl>").unwrap();
+    }
+}
+
+impl Display for Languages {
+    /// Writes one dictionary per line, e.g. `en → es  Oxford Spanish Dictionary`.
+    fn display(&self, output: &mut String) {
+        for dictionary in &self.results {
+            write!(output, "{}", dictionary_codes(dictionary).bold()).unwrap();
+            if let Some(source) = &dictionary.source {
+                write!(output, "  {source}").unwrap();
+            }
+            writeln!(output).unwrap();
+        }
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<ul>").unwrap();
+        for dictionary in &self.results {
+            write!(output, "<li><b>{}</b>", dictionary_codes(dictionary)).unwrap();
+            if let Some(source) = &dictionary.source {
+                write!(output, " {source}").unwrap();
+            }
+            write!(output, "</li>").unwrap();
+        }
+        write!(output, "</ul>").unwrap();
+    }
+}
+
+fn dictionary_codes(dictionary: &Dictionary) -> String {
+    match &dictionary.target_language {
+        Some(target) => format!("{} → {}", dictionary.source_language.id, target.id),
+        None => dictionary.source_language.id.clone(),
+    }
+}
+
+impl Display for EndpointOptions {
+    /// Writes each endpoint followed by its options.
+    fn display(&self, output: &mut String) {
+        for (endpoint, options) in &self.results {
+            writeln!(output, "{}  {}", endpoint.bold(), options.join(", ")).unwrap();
+        }
+    }
+    fn to_html(&self, output: &mut String) {
+        write!(output, "<dl>").unwrap();
+        for (endpoint, options) in &self.results {
+            write!(output, "<dt>{endpoint}</dt><dd>{}</dd>", options.join(", ")).unwrap();
+        }
+        write!(output, "</dl>").unwrap();
+    }
+}
+
+#[test]
+fn test_sense_without_definitions_is_skipped() {
+    let sense: Sense = serde_json::from_str(r#"{"definitions": [], "domains": []}"#).unwrap();
+    let (mut text, mut html) = (String::new(), String::new());
+    sense.display(&mut text);
+    sense.to_html(&mut html);
+    assert_eq!((text.as_str(), html.as_str()), ("", ""));
+
+    let sense: Sense =
+        serde_json::from_str(r#"{"definitions": ["a reddish coating"], "regions": []}"#).unwrap();
+    let mut html = String::new();
+    sense.to_html(&mut html);
+    assert!(html.starts_with("<li>a reddish coating"), "{html}");
+}
@@ -0,0 +1,261 @@
+/*! Errors returned when querying the OD API */
+
+use reqwest::StatusCode;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Everything that can go wrong when looking up a word.
+#[derive(Debug)]
+pub enum OxdError {
+    /// The client was built with unusable settings.
+    InvalidConfig(String),
+    /// The language code is not one the OD API offers for this kind of lookup.
+    UnsupportedLanguage {
+        code: String,
+        supported: &'static [&'static str],
+    },
+    /// The request could not be sent or its response could not be read.
+    Network(reqwest::Error),
+    /// The API rejected the credentials (401 or 403).
+    Unauthorized(StatusCode),
+    /// The API has no entry for the word (404).
+    NotFound(String),
+    /// The API asked us to slow down (429).
+    RateLimited { retry_after: Option<Duration> },
+    /// The monthly request budget of the [rate limiter](crate::rate_limit::RateLimiter)
+    /// is used up, so no request was sent.
+    QuotaExceeded { limit: u32 },
+    /// An offline client has no cached response for the word.
+    NotCached(String),
+    /// The API failed on its side (5xx).
+    Server(StatusCode),
+    /// Any other status the API is not documented to return.
+    UnexpectedStatus(StatusCode),
+    /// The response body does not match the [models](crate::models).
+    Decode {
+        path: String,
+        source: serde_json::Error,
+    },
+    /// A file kept between runs could not be read or written.
+    Io { path: PathBuf, source: io::Error },
+    /// An audio file could not be played, e.g. on a machine without a sound device.
+    Audio(String),
+    /// A DICT server could not be reached, or replied outside the protocol.
+    Dict { url: String, source: io::Error },
+}
+
+impl fmt::Display for OxdError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OxdError::InvalidConfig(message) => write!(f, "invalid client settings: {message}"),
+            OxdError::UnsupportedLanguage { code, supported } => write!(
+                f,
+                "unsupported language \"{code}\", expected one of {}",
+                supported.join(", ")
+            ),
+            OxdError::Network(err) => write!(f, "network error: {err}"),
+            OxdError::Unauthorized(status) => {
+                write!(f, "OD API rejected the credentials ({status})")
+            }
+            OxdError::NotFound(word) => write!(f, "no entry found for \"{word}\""),
+            OxdError::RateLimited { retry_after } => match retry_after {
+                Some(delay) => write!(
+                    f,
+                    "rate limited by OD API, retry after {}s",
+                    delay.as_secs()
+                ),
+                None => write!(f, "rate limited by OD API"),
+            },
+            OxdError::QuotaExceeded { limit } => {
+                write!(f, "monthly quota of {limit} OD API requests is used up")
+            }
+            OxdError::NotCached(word) => {
+                write!(f, "no cached response for \"{word}\" to use offline")
+            }
+            OxdError::Server(status) => write!(f, "OD API server error ({status})"),
+            OxdError::UnexpectedStatus(status) => {
+                write!(f, "unexpected response from OD API ({status})")
+            }
+            OxdError::Decode { path, source } => {
+                write!(f, "cannot decode OD API response at `{path}`: {source}")
+            }
+            OxdError::Io { path, source } => {
+                write!(f, "cannot access {}: {source}", path.display())
+            }
+            OxdError::Audio(message) => write!(f, "cannot play audio: {message}"),
+            OxdError::Dict { url, source } => write!(f, "DICT server {url} failed: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for OxdError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            OxdError::Network(err) => Some(err),
+            OxdError::Decode { source, .. } => Some(source),
+            OxdError::Io { source, .. } => Some(source),
+            OxdError::Dict { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for OxdError {
+    fn from(err: reqwest::Error) -> Self {
+        OxdError::Network(err)
+    }
+}
+
+impl OxdError {
+    /// Whether trying again later may succeed: rate limiting, server errors,
+    /// timeouts and failed connections.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            OxdError::RateLimited { .. } | OxdError::Server(_) => true,
+            OxdError::Network(err) => err.is_timeout() || err.is_connect(),
+            _ => false,
+        }
+    }
+
+    /// An I/O error on `path`.
+    pub fn io(path: &std::path::Path, source: io::Error) -> Self {
+        OxdError::Io {
+            path: path.to_owned(),
+            source,
+        }
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+impl OxdError {
+    /// Maps a non-200 response to an error, or returns `None` on success.
+    pub(crate) fn from_status(
+        status: StatusCode,
+        headers: &reqwest::header::HeaderMap,
+        word: &str,
+    ) -> Option<Self> {
+        match status {
+            StatusCode::OK => None,
+            StatusCode::UNAUTHORIZED | StatusCode::FORBIDDEN => {
+                Some(OxdError::Unauthorized(status))
+            }
+            StatusCode::NOT_FOUND => Some(OxdError::NotFound(word.to_owned())),
+            StatusCode::TOO_MANY_REQUESTS => Some(OxdError::RateLimited {
+                retry_after: retry_after(headers),
+            }),
+            s if s.is_server_error() => Some(OxdError::Server(status)),
+            _ => Some(OxdError::UnexpectedStatus(status)),
+        }
+    }
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+/// Reads a `Retry-After` header, given in seconds or as an HTTP date.
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let value = headers.get(reqwest::header::RETRY_AFTER)?.to_str().ok()?;
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    retry_after_value(value, now.as_secs())
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+/// How long a `Retry-After` value asks to wait when it is `now` seconds since the Unix epoch.
+/// A date already past means no wait.
+fn retry_after_value(value: &str, now: u64) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(secs) = value.parse() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = http_date(value)?;
+    Some(Duration::from_secs(date.saturating_sub(now)))
+}
+
+#[cfg(any(feature = "blocking", feature = "async"))]
+/// Parses the preferred HTTP date format, e.g. `Sun, 06 Nov 1994 08:49:37 GMT`,
+/// into seconds since the Unix epoch. The obsolete formats are not supported.
+fn http_date(value: &str) -> Option<u64> {
+    const MONTHS: [&str; 12] = [
+        "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+    ];
+    let (_weekday, rest) = value.split_once(", ")?;
+    let [day, month, year, time, "GMT"] = rest.split(' ').collect::<Vec<_>>()[..] else {
+        return None;
+    };
+    let day: u64 = day.parse().ok()?;
+    let month = MONTHS.iter().position(|name| *name == month)? as u64 + 1;
+    let year: u64 = year.parse().ok()?;
+    let [hours, minutes, seconds] = time
+        .split(':')
+        .map(|field| field.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?[..]
+    else {
+        return None;
+    };
+    if !(1..=31).contains(&day) || year < 1970 || hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+    // Days since the epoch of a Gregorian date, counting years from March.
+    let (y, m) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = y / 400;
+    let year_of_era = y % 400;
+    let day_of_year = (153 * m + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = (era * 146_097 + day_of_era).checked_sub(719_468)?;
+    Some(days * 86_400 + hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Deserializes a response body, keeping track of where decoding failed.
+pub(crate) fn decode<T: serde::de::DeserializeOwned>(body: &str) -> Result<T, OxdError> {
+    let de = &mut serde_json::Deserializer::from_str(body);
+    serde_path_to_error::deserialize(de).map_err(|err| OxdError::Decode {
+        path: err.path().to_string(),
+        source: err.into_inner(),
+    })
+}
+
+#[test]
+#[cfg(any(feature = "blocking", feature = "async"))]
+fn test_retry_after_value() {
+    assert_eq!(
+        retry_after_value(" 120 ", 0),
+        Some(Duration::from_secs(120))
+    );
+    assert_eq!(http_date("Thu, 01 Jan 1970 00:00:00 GMT"), Some(0));
+    assert_eq!(
+        http_date("Sun, 06 Nov 1994 08:49:37 GMT"),
+        Some(784_111_777)
+    );
+    assert_eq!(
+        http_date("Tue, 29 Feb 2028 12:00:00 GMT"),
+        Some(1_835_438_400)
+    );
+    assert_eq!(
+        retry_after_value("Sun, 06 Nov 1994 08:49:37 GMT", 784_111_700),
+        Some(Duration::from_secs(77))
+    );
+    assert_eq!(
+        retry_after_value("Sun, 06 Nov 1994 08:49:37 GMT", 784_200_000),
+        Some(Duration::ZERO)
+    );
+    assert_eq!(retry_after_value("Sunday, 06-Nov-94 08:49:37 GMT", 0), None);
+    assert_eq!(retry_after_value("soon", 0), None);
+}
+
+#[test]
+fn test_decode_reports_path() {
+    let body = r#"{"metadata": {}, "results": [{"id": "rust", "language": "en-us",
+        "lexicalEntries": [{"entries": [], "language": "en-us", "text": "rust"}],
+        "type": "headword", "word": "rust"}]}"#;
+    let err = decode::<crate::models::RetrieveEntry>(body).unwrap_err();
+    let OxdError::Decode { path, .. } = err else {
+        panic!("expected a decode error, got {err:?}");
+    };
+    assert_eq!(path, "results[0].lexicalEntries[0]");
+}
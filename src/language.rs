@@ -0,0 +1,172 @@
+/*! Languages available from the OD API */
+
+use crate::error::OxdError;
+use std::fmt;
+use std::str::FromStr;
+
+/// A monolingual dictionary offered by the OD API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum Language {
+    /// British English
+    EnGb,
+    /// American English
+    #[default]
+    EnUs,
+    /// Spanish
+    Es,
+    /// French
+    Fr,
+    /// Gujarati
+    Gu,
+    /// Hindi
+    Hi,
+    /// Latvian
+    Lv,
+    /// Romanian
+    Ro,
+    /// Swahili
+    Sw,
+    /// Tamil
+    Ta,
+}
+
+impl Language {
+    /// Every language the entries endpoints accept.
+    pub const ALL: [Language; 10] = [
+        Language::EnGb,
+        Language::EnUs,
+        Language::Es,
+        Language::Fr,
+        Language::Gu,
+        Language::Hi,
+        Language::Lv,
+        Language::Ro,
+        Language::Sw,
+        Language::Ta,
+    ];
+
+    /// Codes of [Language::ALL], in the same order.
+    pub const CODES: [&'static str; 10] = [
+        "en-gb", "en-us", "es", "fr", "gu", "hi", "lv", "ro", "sw", "ta",
+    ];
+
+    /// The code used in OD API paths, e.g. `en-gb`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Language::EnGb => "en-gb",
+            Language::EnUs => "en-us",
+            Language::Es => "es",
+            Language::Fr => "fr",
+            Language::Gu => "gu",
+            Language::Hi => "hi",
+            Language::Lv => "lv",
+            Language::Ro => "ro",
+            Language::Sw => "sw",
+            Language::Ta => "ta",
+        }
+    }
+
+    /// The code of the dataset behind the lemmas, sentences and thesaurus endpoints,
+    /// which don't tell British from American English: `en` for both.
+    pub fn dataset_code(&self) -> &'static str {
+        match self {
+            Language::EnGb | Language::EnUs => "en",
+            _ => self.code(),
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for Language {
+    type Err = OxdError;
+
+    /// Parses a language code, accepting `en_GB` as well as `en-gb`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = normalize_code(s);
+        Language::ALL
+            .into_iter()
+            .find(|lang| lang.code() == code)
+            .ok_or_else(|| OxdError::UnsupportedLanguage {
+                code: s.to_owned(),
+                supported: &Language::CODES,
+            })
+    }
+}
+
+/// A language of the bilingual dictionaries behind the translations endpoint.
+///
+/// Not every pair of these languages is available;
+/// most dictionaries translate from or into English.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TranslationLanguage(&'static str);
+
+impl TranslationLanguage {
+    /// Every code the translations endpoint accepts.
+    pub const CODES: [&'static str; 32] = [
+        "ar", "de", "el", "en", "es", "fr", "gu", "ha", "hi", "id", "ig", "it", "ka", "lv", "mr",
+        "ms", "nso", "pt", "qu", "ro", "ru", "sw", "ta", "tg", "tk", "tn", "tt", "uk", "xh", "yo",
+        "zh", "zu",
+    ];
+
+    /// English, the language most bilingual dictionaries start from.
+    pub const ENGLISH: TranslationLanguage = TranslationLanguage("en");
+
+    /// The code used in OD API paths, e.g. `es`.
+    pub fn code(&self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for TranslationLanguage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.code())
+    }
+}
+
+impl FromStr for TranslationLanguage {
+    type Err = OxdError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let code = normalize_code(s);
+        TranslationLanguage::CODES
+            .into_iter()
+            .find(|supported| *supported == code)
+            .map(TranslationLanguage)
+            .ok_or_else(|| OxdError::UnsupportedLanguage {
+                code: s.to_owned(),
+                supported: &TranslationLanguage::CODES,
+            })
+    }
+}
+
+fn normalize_code(s: &str) -> String {
+    s.trim().replace('_', "-").to_ascii_lowercase()
+}
+
+#[test]
+fn test_parse_language() {
+    assert_eq!("en-gb".parse::<Language>().unwrap(), Language::EnGb);
+    assert_eq!("en_US".parse::<Language>().unwrap(), Language::EnUs);
+    assert_eq!("es".parse::<Language>().unwrap(), Language::Es);
+    assert!("en".parse::<Language>().is_err());
+    assert!("xx".parse::<Language>().is_err());
+}
+
+#[test]
+fn test_dataset_code() {
+    assert_eq!(Language::EnGb.dataset_code(), "en");
+    assert_eq!(Language::EnUs.dataset_code(), "en");
+    assert_eq!(Language::Es.dataset_code(), "es");
+}
+
+#[test]
+fn test_parse_translation_language() {
+    assert_eq!("EN".parse::<TranslationLanguage>().unwrap().code(), "en");
+    assert_eq!("nso".parse::<TranslationLanguage>().unwrap().code(), "nso");
+    assert!("en-us".parse::<TranslationLanguage>().is_err());
+}
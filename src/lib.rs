@@ -1,7 +1,11 @@
 /*!
 Oxd is a client library for the Oxford Dictionary API.
 It provides [a series of structs](models) modeling entries returned from the API,
-a function [get_entry] to get entries from the API,
+an `OxdClient` to get entries from the API,
+a [DictionaryBackend] trait for the sources of entries, including a local [WordNet](wordnet::WordNet),
+[StarDict](stardict::StarDict) dictionaries, [Wiktionary](wiktionary::Wiktionary) extracts
+and [DICT servers](dict::DictClient),
+an [OxdError] describing why a lookup failed,
 a [Display](display::Display) trait to display entries,
 and a [Pronounce](pronounce::Pronounce) trait to play pronunciation files.
 
@@ -25,75 +29,61 @@ Then just type `oxd rust` to look up the word "rust".
 
 ## Use as a library
 
-```rust
-use oxd::{build_client, get_entry};
-
-let app_id = "your_app_id".to_owned();
-let app_key = "your_app_key".to_owned();
-
-let client = build_client(app_id, app_key);
-if let Some(retrieve_entry) = get_entry(&client, "rust") {
-    println!("{:#?}", retrieve_entry);
+```no_run
+use oxd::OxdClient;
+
+let client = OxdClient::builder()
+    .app_id("your_app_id")
+    .app_key("your_app_key")
+    .build()
+    .expect("credentials should be valid header values");
+match client.entry("rust") {
+    Ok(retrieve_entry) => println!("{:#?}", retrieve_entry),
+    Err(err) => eprintln!("{err}"),
 }
 ```
-*/
 
-use reqwest::header::{self, HeaderValue};
-use reqwest::{blocking, StatusCode, Url};
+## Cargo features
+
+- `blocking` (default): the blocking `OxdClient`.
+- `sound` (default): the [Pronounce](pronounce::Pronounce) trait, playing audio through rodio.
+  [Pronounce::pronounce](pronounce::Pronounce::pronounce) also needs `blocking`.
+- `async`: an `AsyncOxdClient` with the same lookups, for use inside async runtimes.
+  Build it with `OxdClientBuilder::build_async`. Retries wait on tokio's timer.
+*/
 
+#[cfg(feature = "async")]
+mod async_client;
+pub mod backend;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub mod cache;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod client;
+pub mod dict;
+#[cfg(any(feature = "blocking", feature = "async"))]
+mod dirs;
 pub mod display;
+pub mod error;
+pub mod language;
+pub mod lookup;
 pub mod models;
+#[cfg(feature = "sound")]
 pub mod pronounce;
-
-const OD_API_BASE_URL: &str = "https://od-api.oxforddictionaries.com/api/v2/";
-
-/// Builds a blocking client from OD API credentials.
-pub fn build_client(app_id: String, app_key: String) -> blocking::Client {
-    let expect = "Should be able to construct HeaderValue from String";
-    let mut headers = header::HeaderMap::new();
-    headers.insert("app_id", HeaderValue::from_str(&app_id).expect(expect));
-    headers.insert("app_key", HeaderValue::from_str(&app_key).expect(expect));
-
-    blocking::Client::builder()
-        .default_headers(headers)
-        .build()
-        .expect("Should be able to build a client without issues")
-}
-
-fn build_full_url(word: &str) -> Url {
-    let base = Url::parse(OD_API_BASE_URL).unwrap();
-    let mut url = base.join("words/en-us").unwrap();
-    url.query_pairs_mut().append_pair("q", word);
-    // println!("{}", url);
-    url
-}
-
-/// Queries the API and returns a single [RetrieveEntry](models::RetrieveEntry).
-pub fn get_entry(client: &blocking::Client, word: &str) -> Option<models::RetrieveEntry> {
-    let full_url = build_full_url(word);
-    let res = client.get(full_url).send().unwrap();
-    if res.status() != StatusCode::OK {
-        eprintln!("Get {} from OD API when querying {}", res.status(), word);
-        return None;
-    }
-    let body: models::RetrieveEntry = serde_json::from_reader(res).unwrap();
-    Some(body)
-}
-
-/// Queries the API and returns a vector of [RetrieveEntry](models::RetrieveEntry)s.
-///
-/// Including possible root forms of this entry.
-pub fn get_entries(client: &blocking::Client, word: &str) -> Vec<models::RetrieveEntry> {
-    let mut entries: Vec<models::RetrieveEntry> = vec![];
-    let Some(entry) = get_entry(client, word) else {
-        return entries;
-    };
-    for root in models::roots(&entry) {
-        let Some(root_entry) = get_entry(client, &root.text) else {
-            continue;
-        };
-        entries.push(root_entry);
-    }
-    entries.push(entry);
-    entries
-}
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub mod rate_limit;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub mod retry;
+pub mod stardict;
+pub mod wiktionary;
+pub mod wordnet;
+
+#[cfg(feature = "async")]
+pub use async_client::AsyncOxdClient;
+pub use backend::DictionaryBackend;
+#[cfg(feature = "blocking")]
+pub use client::OxdClient;
+#[cfg(any(feature = "blocking", feature = "async"))]
+pub use client::OxdClientBuilder;
+pub use error::OxdError;
+pub use language::{Language, TranslationLanguage};
+pub use lookup::{Lookup, LookupFilters};
@@ -0,0 +1,437 @@
+/*! Results of looking up a word together with its headwords and root forms */
+
+use crate::error::OxdError;
+use crate::models::lemmas::Lemmatron;
+use crate::models::{Entry, GrammaticalFeature, LexicalEntry, RetrieveEntry, Sense};
+use std::collections::HashSet;
+
+/// How many derivation steps lookups follow from headwords to root forms, unless told otherwise.
+pub const DEFAULT_ROOT_DEPTH: usize = 3;
+
+/// Turns a word into the id OD uses in paths, e.g. `ice_cream` for "Ice cream".
+pub(crate) fn word_id(word: &str) -> String {
+    word.trim().to_lowercase().replace(' ', "_")
+}
+
+/// Everything found when looking up a word.
+#[derive(Debug, Clone)]
+pub struct Lookup {
+    /// The word as it was looked up, e.g. "running".
+    pub word: String,
+    /// How the word inflects from its headwords, e.g. "running" from "run".
+    ///
+    /// `None` when the lemmas endpoint does not know the word.
+    pub lemmas: Option<Lemmatron>,
+    /// Ids of the headwords the word resolved to, e.g. `run` for "running".
+    pub headwords: Vec<String>,
+    /// The entries of headwords and root forms, each once, in the order they were found.
+    pub entries: Vec<LookupEntry>,
+}
+
+/// An entry found while looking up a word, with how it relates to the others.
+#[derive(Debug, Clone)]
+pub struct LookupEntry {
+    /// The headword id, e.g. `rusty`.
+    pub id: String,
+    pub entry: RetrieveEntry,
+    /// Ids of the words this one is a derivative of, e.g. `rust` for `rusty`,
+    /// whether or not their entries were fetched.
+    pub derivative_of: Vec<String>,
+    /// How many derivation steps lead from a headword to this entry, 0 for headwords.
+    pub depth: usize,
+}
+
+impl Lookup {
+    /// The entry of a headword or root form by its id.
+    pub fn get(&self, id: &str) -> Option<&LookupEntry> {
+        self.entries.iter().find(|entry| entry.id == id)
+    }
+
+    /// The fetched entries of the words `id` is a derivative of.
+    pub fn roots_of(&self, id: &str) -> Vec<&LookupEntry> {
+        self.get(id).map_or(vec![], |entry| {
+            entry
+                .derivative_of
+                .iter()
+                .filter_map(|root| self.get(root))
+                .collect()
+        })
+    }
+
+    /// The fetched entries derived from `id`.
+    pub fn derivatives_of(&self, id: &str) -> Vec<&LookupEntry> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.derivative_of.iter().any(|root| root == id))
+            .collect()
+    }
+
+    /// The entries in the order oxd shows them: headwords in turn,
+    /// each preceded by its root forms, roots of roots first.
+    pub fn in_display_order(&self) -> Vec<&LookupEntry> {
+        let mut seen = HashSet::new();
+        let mut ordered = vec![];
+        for id in self
+            .headwords
+            .iter()
+            .chain(self.entries.iter().map(|e| &e.id))
+        {
+            self.push_with_roots(id, &mut seen, &mut ordered);
+        }
+        ordered
+    }
+
+    /// Takes the entries apart in [display order](Self::in_display_order).
+    pub fn into_display_order(self) -> Vec<RetrieveEntry> {
+        let order: Vec<String> = self
+            .in_display_order()
+            .into_iter()
+            .map(|entry| entry.id.clone())
+            .collect();
+        let mut entries = self.entries;
+        order
+            .iter()
+            .filter_map(|id| {
+                let i = entries.iter().position(|entry| &entry.id == id)?;
+                Some(entries.swap_remove(i).entry)
+            })
+            .collect()
+    }
+
+    /// Adds an entry after its roots, unless it was added already,
+    /// which also ends cycles.
+    fn push_with_roots<'a>(
+        &'a self,
+        id: &str,
+        seen: &mut HashSet<&'a str>,
+        ordered: &mut Vec<&'a LookupEntry>,
+    ) {
+        let Some(entry) = self.get(id) else {
+            return;
+        };
+        if !seen.insert(&entry.id) {
+            return;
+        }
+        for root in &entry.derivative_of {
+            self.push_with_roots(root, seen, ordered);
+        }
+        ordered.push(entry);
+    }
+}
+
+/// Walks from the headwords of a lookup to their root forms, breadth first.
+///
+/// Each id is fetched once, however many entries derive from it,
+/// so cycles in `derivativeOf` end the walk instead of looping.
+pub(crate) struct RootResolver {
+    word: String,
+    lemmas: Option<Lemmatron>,
+    /// Ids and texts still to fetch, with their depth.
+    queue: std::collections::VecDeque<(String, String, usize)>,
+    visited: HashSet<String>,
+    max_depth: usize,
+    headwords: Vec<String>,
+    entries: Vec<LookupEntry>,
+}
+
+impl RootResolver {
+    /// Starts from the headwords the lemmas endpoint gave for `word`,
+    /// or from the word itself when the endpoint does not know it,
+    /// following roots up to `max_depth` steps.
+    pub(crate) fn start(
+        word: &str,
+        lemmas: Result<Lemmatron, OxdError>,
+        max_depth: usize,
+    ) -> Result<Self, OxdError> {
+        let lemmas = match lemmas {
+            Ok(lemmatron) => Some(lemmatron),
+            Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => None,
+            Err(err) => return Err(err),
+        };
+        let headwords: Vec<(String, String)> = match &lemmas {
+            Some(lemmatron) => crate::models::lemmas::lemmas(lemmatron)
+                .into_iter()
+                .map(|lemma| (lemma.id, lemma.text))
+                .collect(),
+            None => vec![(word_id(word), word.to_owned())],
+        };
+        let mut resolver = RootResolver {
+            word: word.to_owned(),
+            lemmas,
+            queue: Default::default(),
+            visited: Default::default(),
+            max_depth,
+            headwords: vec![],
+            entries: vec![],
+        };
+        for (id, text) in headwords {
+            if resolver.visited.insert(id.clone()) {
+                resolver.headwords.push(id.clone());
+                resolver.queue.push_back((id, text, 0));
+            }
+        }
+        Ok(resolver)
+    }
+
+    /// The text of the next word to fetch.
+    pub(crate) fn next(&self) -> Option<&str> {
+        self.queue.front().map(|(_, text, _)| text.as_str())
+    }
+
+    /// Records the entry fetched for [next](Self::next) and queues its roots.
+    ///
+    /// Missing entries, and entries left empty by filters, are skipped;
+    /// any other error ends the lookup.
+    pub(crate) fn found(&mut self, entry: Result<RetrieveEntry, OxdError>) -> Result<(), OxdError> {
+        let Some((id, _, depth)) = self.queue.pop_front() else {
+            return Ok(());
+        };
+        let entry = match entry {
+            Ok(entry) if !entry.headword_entries.is_empty() => entry,
+            Ok(_) | Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => return Ok(()),
+            Err(err) => return Err(err),
+        };
+        let mut derivative_of: Vec<String> = vec![];
+        for root in crate::models::roots(&entry) {
+            if derivative_of.contains(&root.id) {
+                continue;
+            }
+            derivative_of.push(root.id.clone());
+            if depth < self.max_depth && self.visited.insert(root.id.clone()) {
+                self.queue.push_back((root.id, root.text, depth + 1));
+            }
+        }
+        self.entries.push(LookupEntry {
+            id,
+            entry,
+            derivative_of,
+            depth,
+        });
+        Ok(())
+    }
+
+    /// The lookup, or [NotFound](OxdError::NotFound) if no entry was found.
+    pub(crate) fn finish(self) -> Result<Lookup, OxdError> {
+        if self.entries.is_empty() {
+            return Err(OxdError::NotFound(self.word));
+        }
+        Ok(Lookup {
+            word: self.word,
+            lemmas: self.lemmas,
+            headwords: self.headwords,
+            entries: self.entries,
+        })
+    }
+}
+
+/// Narrows a lookup down, e.g. to the verb senses of "run".
+///
+/// Filters are sent as OD query parameters. The ids they take are listed by
+/// the [catalog](crate::models::catalog) endpoints, e.g. `verb` or `computing`.
+/// Each filter matches any of its values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LookupFilters {
+    pub lexical_categories: Vec<String>,
+    pub domains: Vec<String>,
+    pub registers: Vec<String>,
+    pub grammatical_features: Vec<String>,
+    /// Parts of the entry to return, e.g. `definitions`. Not applied by [prune](Self::prune).
+    pub fields: Vec<String>,
+    /// Whether the word must match a headword exactly, ignoring case.
+    pub strict_match: Option<bool>,
+}
+
+impl LookupFilters {
+    /// Keeps lexical entries of this part of speech, e.g. `verb`.
+    pub fn lexical_category(mut self, id: impl Into<String>) -> Self {
+        self.lexical_categories.push(id.into());
+        self
+    }
+
+    /// Keeps senses of this subject area, e.g. `computing`.
+    pub fn domain(mut self, id: impl Into<String>) -> Self {
+        self.domains.push(id.into());
+        self
+    }
+
+    /// Keeps senses of this register, e.g. `informal`.
+    pub fn register(mut self, id: impl Into<String>) -> Self {
+        self.registers.push(id.into());
+        self
+    }
+
+    /// Keeps entries with this grammatical feature, e.g. `plural`.
+    pub fn grammatical_feature(mut self, id: impl Into<String>) -> Self {
+        self.grammatical_features.push(id.into());
+        self
+    }
+
+    /// Asks only for this part of the entry, e.g. `pronunciations`.
+    pub fn field(mut self, name: impl Into<String>) -> Self {
+        self.fields.push(name.into());
+        self
+    }
+
+    pub fn strict_match(mut self, strict_match: bool) -> Self {
+        self.strict_match = Some(strict_match);
+        self
+    }
+
+    /// Whether no filter is set.
+    pub fn is_empty(&self) -> bool {
+        *self == LookupFilters::default()
+    }
+
+    /// The OD query parameters, with multiple values separated by commas.
+    pub fn query(&self) -> Vec<(&'static str, String)> {
+        let mut query = vec![];
+        let lists = [
+            ("lexicalCategory", &self.lexical_categories),
+            ("domains", &self.domains),
+            ("registers", &self.registers),
+            ("grammaticalFeatures", &self.grammatical_features),
+            ("fields", &self.fields),
+        ];
+        for (name, values) in lists {
+            if !values.is_empty() {
+                query.push((name, values.join(",")));
+            }
+        }
+        if let Some(strict_match) = self.strict_match {
+            query.push(("strictMatch", strict_match.to_string()));
+        }
+        query
+    }
+
+    /// Prunes an entry fetched with these filters, reporting one left empty
+    /// as [NotFound](OxdError::NotFound).
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub(crate) fn narrow(
+        &self,
+        mut entry: RetrieveEntry,
+        word: &str,
+    ) -> Result<RetrieveEntry, OxdError> {
+        self.prune(&mut entry);
+        if entry.headword_entries.is_empty() {
+            return Err(OxdError::NotFound(word.to_owned()));
+        }
+        Ok(entry)
+    }
+
+    /// Removes what the filters exclude, for endpoints that ignore them.
+    ///
+    /// A sense outside the requested domains and registers is kept
+    /// when one of its subsenses matches, with only the matching subsenses.
+    /// Entries left without senses, and headwords left without entries, are removed too.
+    pub fn prune(&self, retrieve_entry: &mut RetrieveEntry) {
+        if self.is_empty() {
+            return;
+        }
+        for headword in &mut retrieve_entry.headword_entries {
+            headword
+                .lexical_entries
+                .retain(|lexical_entry| self.keeps_lexical_entry(lexical_entry));
+            for lexical_entry in &mut headword.lexical_entries {
+                let features = &lexical_entry.grammatical_features;
+                lexical_entry
+                    .entries
+                    .retain(|entry| self.keeps_features(entry, features));
+                if self.filters_senses() {
+                    for entry in &mut lexical_entry.entries {
+                        if let Some(senses) = &mut entry.senses {
+                            self.prune_senses(senses);
+                        }
+                    }
+                    lexical_entry.entries.retain(|entry| {
+                        entry
+                            .senses
+                            .as_ref()
+                            .is_some_and(|senses| !senses.is_empty())
+                    });
+                }
+            }
+            headword
+                .lexical_entries
+                .retain(|lexical_entry| !lexical_entry.entries.is_empty());
+        }
+        retrieve_entry
+            .headword_entries
+            .retain(|headword| !headword.lexical_entries.is_empty());
+    }
+
+    fn keeps_lexical_entry(&self, lexical_entry: &LexicalEntry) -> bool {
+        let category = &lexical_entry.lexical_category;
+        matches_any(&self.lexical_categories, [(&category.id, &category.text)])
+    }
+
+    fn keeps_features(
+        &self,
+        entry: &Entry,
+        lexical_features: &Option<Vec<GrammaticalFeature>>,
+    ) -> bool {
+        let features = entry
+            .grammatical_features
+            .iter()
+            .chain(lexical_features)
+            .flatten()
+            .map(|feature| (&feature.id, &feature.text));
+        matches_any(&self.grammatical_features, features)
+    }
+
+    fn filters_senses(&self) -> bool {
+        !self.domains.is_empty() || !self.registers.is_empty()
+    }
+
+    fn prune_senses(&self, senses: &mut Vec<Sense>) {
+        senses.retain_mut(|sense| {
+            if self.keeps_sense(sense) {
+                return true;
+            }
+            match &mut sense.subsenses {
+                Some(subsenses) => {
+                    self.prune_senses(subsenses);
+                    !subsenses.is_empty()
+                }
+                None => false,
+            }
+        });
+    }
+
+    fn keeps_sense(&self, sense: &Sense) -> bool {
+        let domains = sense.domains.iter().flatten();
+        let registers = sense.registers.iter().flatten();
+        matches_any(&self.domains, domains.map(|d| (&d.id, &d.text)))
+            && matches_any(&self.registers, registers.map(|r| (&r.id, &r.text)))
+    }
+}
+
+/// Whether a label matches one of the wanted ids, or nothing is wanted.
+fn matches_any<'a>(
+    wanted: &[String],
+    labels: impl IntoIterator<Item = (&'a String, &'a String)>,
+) -> bool {
+    wanted.is_empty()
+        || labels.into_iter().any(|(id, text)| {
+            wanted
+                .iter()
+                .any(|w| w.eq_ignore_ascii_case(id) || w.eq_ignore_ascii_case(text))
+        })
+}
+
+#[test]
+fn test_filters_query() {
+    let filters = LookupFilters::default()
+        .lexical_category("verb")
+        .domain("computing")
+        .domain("sport")
+        .strict_match(false);
+    assert_eq!(
+        filters.query(),
+        [
+            ("lexicalCategory", "verb".to_owned()),
+            ("domains", "computing,sport".to_owned()),
+            ("strictMatch", "false".to_owned()),
+        ]
+    );
+    assert!(LookupFilters::default().query().is_empty());
+}
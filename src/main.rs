@@ -1,37 +1,307 @@
+use oxd::cache::ResponseCache;
+use oxd::dict::{DictClient, DictServer};
 use oxd::display::Display;
+use oxd::models::search::SearchResults;
+use oxd::models::sentences::attach_sentences;
 use oxd::models::RetrieveEntry;
 use oxd::pronounce::Pronounce;
-use oxd::{build_client, get_entries};
+use oxd::rate_limit::{Plan, RateLimiter};
+use oxd::retry::RetryPolicy;
+use oxd::stardict::StarDict;
+use oxd::wiktionary::{self, Wiktionary};
+use oxd::wordnet::WordNet;
+use oxd::{DictionaryBackend, LookupFilters, OxdClient, OxdError};
+use std::io::{self, IsTerminal};
+use std::net::TcpListener;
+use std::path::Path;
 use std::{env, process};
 
 mod args;
-use args::OxdArgs;
+mod cache_command;
+mod prefetch;
+use args::{CatalogKind, Command, OxdArgs, Source};
 use clap::Parser;
 
 fn main() {
     let args = OxdArgs::parse();
+    let filters = args.filters();
 
+    // Managing the cache needs neither credentials nor a client.
+    if let Some(Command::Cache { action }) = &args.command {
+        cache_command::run(action).unwrap_or_else(exit_with);
+        return;
+    }
+    if let Some(Command::ImportWiktionary { file }) = &args.command {
+        import_wiktionary(file).unwrap_or_else(exit_with);
+        return;
+    }
+    if args.sentences > 0 && args.source != Source::Od {
+        eprintln!("Only --source od has corpus sentences, so --sentences cannot be used with --source {}.", args.source);
+        process::exit(1);
+    }
+    // Other sources need no OD credentials either.
+    if let Some(backend) = source_backend(&args) {
+        match (&args.command, &args.word) {
+            (None, Some(word)) => show_lookup(backend.as_ref(), word, &filters, args.sound, |_| {}),
+            (Some(Command::ServeDict { port, bind }), _) => serve_dict(
+                backend.as_ref(),
+                &format!("oxd --source {}", args.source),
+                bind,
+                *port,
+            ),
+            _ => {
+                eprintln!("Only word lookups and serve-dict can use a --source other than od.");
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    let prefetching = matches!(args.command, Some(Command::Prefetch { .. }));
+    if prefetching && (args.no_cache || args.offline) {
+        eprintln!("Prefetching fills the cache, so it cannot run with --no-cache or --offline.");
+        process::exit(1);
+    }
+
+    // Offline lookups never reach the API, so they work without credentials.
     let app_id = env::var("OD_API_APP_ID").unwrap_or_else(|err| {
+        if args.offline {
+            return String::new();
+        }
         eprintln!("Problem reading Oxford Dictionary API App ID: {err}\nGet one at https://developer.oxforddictionaries.com/ and set OD_API_APP_ID.");
         process::exit(1);
     });
     let app_key = env::var("OD_API_APP_KEY").unwrap_or_else(|err| {
+        if args.offline {
+            return String::new();
+        }
         eprintln!("Problem reading Oxford Dictionary API App KEY: {err}\nGet one at https://developer.oxforddictionaries.com/ and set OD_API_APP_KEY.");
         process::exit(1);
     });
 
-    let client = build_client(app_id, app_key);
+    let mut builder = OxdClient::builder()
+        .app_id(app_id)
+        .app_key(app_key)
+        .source_lang(args.lang);
+    // Requests go unthrottled unless the user names their plan. Invocations then share
+    // the monthly count, so the plan's quota holds across runs.
+    if let Ok(plan) = env::var("OD_API_PLAN") {
+        let plan: Plan = plan.parse().unwrap_or_else(exit_with);
+        builder = builder.rate_limiter(RateLimiter::persistent(plan).unwrap_or_else(exit_with));
+    }
+    // A long word list should ride out the odd server hiccup instead of recording a failure.
+    if prefetching {
+        builder = builder.retry(RetryPolicy::new(4).on_retry(|event| {
+            eprintln!(
+                "{}; retrying in {}s",
+                event.error,
+                event.delay.as_secs_f32().ceil()
+            )
+        }));
+    }
+    if !args.no_cache {
+        let cache = ResponseCache::open_default().unwrap_or_else(exit_with);
+        builder = builder
+            .cache(cache.refresh(args.refresh))
+            .offline(args.offline);
+    }
+    let client = builder.build().unwrap_or_else(exit_with);
+
+    match (args.command, args.word) {
+        (Some(Command::Syn { word }), _) => {
+            let thesaurus = lookup_or_suggest(
+                &word,
+                |word| client.thesaurus(word),
+                |word| client.search_thesaurus(word, SUGGESTIONS),
+            );
+            let mut canvas = String::new();
+            thesaurus.display(&mut canvas);
+            println!("{canvas}");
+        }
+        (Some(Command::Translate { from, to, word }), _) => {
+            let entry = client
+                .translations(from, to, &word)
+                .unwrap_or_else(exit_with);
+            output_entry(entry, &client, args.sound);
+        }
+        (Some(Command::Catalog { kind }), _) => {
+            let canvas = catalog(&client, kind).unwrap_or_else(exit_with);
+            print!("{canvas}");
+        }
+        (Some(Command::Prefetch { file, restart }), _) => {
+            prefetch::run(&client, &file, restart).unwrap_or_else(exit_with);
+        }
+        (Some(Command::ServeDict { port, bind }), _) => {
+            let description = format!("Oxford Dictionaries ({})", args.lang.code());
+            serve_dict(&client, &description, &bind, port);
+        }
+        (Some(Command::Cache { .. } | Command::ImportWiktionary { .. }), _) => {
+            unreachable!("handled before building the client")
+        }
+        (None, Some(word)) => {
+            show_lookup(&client, &word, &filters, args.sound, |retrieve_entry| {
+                if args.sentences > 0 {
+                    add_sentences(&client, retrieve_entry, args.sentences);
+                }
+            });
+        }
+        (None, None) => unreachable!("clap requires a word without subcommand"),
+    }
+}
+
+fn exit_with<T>(err: OxdError) -> T {
+    eprintln!("{err}");
+    process::exit(1);
+}
+
+/// How many similar words to suggest when a word is not found.
+const SUGGESTIONS: usize = 5;
+
+/// Runs a lookup. When the word is unknown, suggests similar words
+/// and, on a terminal, offers to look one of them up instead.
+fn lookup_or_suggest<T>(
+    word: &str,
+    lookup: impl Fn(&str) -> Result<T, OxdError>,
+    search: impl Fn(&str) -> Result<SearchResults, OxdError>,
+) -> T {
+    match lookup(word) {
+        Ok(found) => found,
+        Err(OxdError::NotFound(_)) => {
+            eprintln!("No entry found for \"{word}\".");
+            let results = search(word).unwrap_or_else(exit_with);
+            let ranked = results.ranked();
+            if ranked.is_empty() {
+                process::exit(1);
+            }
+            let mut canvas = String::new();
+            results.display(&mut canvas);
+            eprint!("Did you mean:\n{canvas}");
+            match choose(ranked.len()) {
+                Some(choice) => lookup(&ranked[choice].word).unwrap_or_else(exit_with),
+                None => process::exit(1),
+            }
+        }
+        Err(err) => exit_with(err),
+    }
+}
+
+/// Asks which suggestion to look up, if someone is at the terminal.
+fn choose(count: usize) -> Option<usize> {
+    if !io::stdin().is_terminal() || !io::stderr().is_terminal() {
+        return None;
+    }
+    eprint!("Look up [1-{count}], or press Enter to quit: ");
+    let mut answer = String::new();
+    io::stdin().read_line(&mut answer).ok()?;
+    let choice: usize = answer.trim().parse().ok()?;
+    (1..=count).contains(&choice).then_some(choice - 1)
+}
+
+/// Opens the dictionary chosen with `--source`, unless it is the OD API.
+fn source_backend(args: &OxdArgs) -> Option<Box<dyn DictionaryBackend + Sync>> {
+    let backend: Box<dyn DictionaryBackend + Sync> = match &args.source {
+        Source::Od => return None,
+        Source::Wordnet => Box::new(
+            match &args.wordnet_dir {
+                Some(dir) => WordNet::open(dir),
+                None => WordNet::open_default(),
+            }
+            .unwrap_or_else(exit_with),
+        ),
+        Source::Stardict(path) => Box::new(StarDict::open(path).unwrap_or_else(exit_with)),
+        Source::Wiktionary(code) => {
+            // Wiktionary has one English, where OD has British and American dictionaries.
+            let code = code
+                .as_deref()
+                .unwrap_or_else(|| args.lang.code().split('-').next().unwrap_or_default());
+            let dir = Wiktionary::default_dir().unwrap_or_else(exit_with);
+            Box::new(Wiktionary::open(dir, code).unwrap_or_else(exit_with))
+        }
+        Source::Dict(url) => Box::new(DictClient::from_url(url).unwrap_or_else(exit_with)),
+    };
+    Some(backend)
+}
+
+/// Answers DICT clients with lookups on `backend` until the process is stopped.
+fn serve_dict(backend: &(dyn DictionaryBackend + Sync), description: &str, bind: &str, port: u16) {
+    let listener = TcpListener::bind((bind, port)).unwrap_or_else(|err| {
+        eprintln!("Cannot listen on {bind}:{port}: {err}");
+        process::exit(1);
+    });
+    eprintln!("Serving DICT on {bind}:{port}");
+    DictServer::new(backend, description).serve(listener);
+}
+
+/// Reads a kaikki.org extract into the store `--source wiktionary` reads.
+fn import_wiktionary(file: &Path) -> Result<(), OxdError> {
+    let summary = wiktionary::import_file(file, Wiktionary::default_dir()?)?;
+    for (code, records) in &summary.records {
+        println!("{code}: {records} records");
+    }
+    if summary.skipped > 0 {
+        println!(
+            "Skipped {} lines that are not word records.",
+            summary.skipped
+        );
+    }
+    Ok(())
+}
+
+/// Looks up a word with its headwords and roots, and prints each entry
+/// after `extend` has added whatever the backend cannot give.
+fn show_lookup(
+    backend: &dyn DictionaryBackend,
+    word: &str,
+    filters: &LookupFilters,
+    sound: bool,
+    extend: impl Fn(&mut RetrieveEntry),
+) {
+    let lookup = lookup_or_suggest(
+        word,
+        |word| backend.lookup_with(word, filters),
+        |word| backend.search(word, SUGGESTIONS),
+    );
+    let mut canvas = String::new();
+    lookup.lemmas.display(&mut canvas);
+    print!("{canvas}");
+    for mut retrieve_entry in lookup.into_display_order() {
+        extend(&mut retrieve_entry);
+        output_entry(retrieve_entry, backend, sound);
+    }
+}
+
+/// Renders the list of values a utility endpoint serves.
+fn catalog(client: &OxdClient, kind: CatalogKind) -> Result<String, OxdError> {
+    let mut canvas = String::new();
+    match kind.label_kind() {
+        Some(label_kind) => client.labels(label_kind)?.display(&mut canvas),
+        None => match kind {
+            CatalogKind::Languages => client.languages()?.display(&mut canvas),
+            CatalogKind::Fields => client.fields()?.display(&mut canvas),
+            _ => client.filters()?.display(&mut canvas),
+        },
+    }
+    Ok(canvas)
+}
 
-    for retrieve_entry in get_entries(&client, &args.word) {
-        output_entry(retrieve_entry, args.sound);
+/// Attaches corpus sentences, leaving the entry as it is if there are none.
+fn add_sentences(client: &OxdClient, retrieve_entry: &mut RetrieveEntry, limit: usize) {
+    let Some(headword) = retrieve_entry.headword_entries.first() else {
+        return;
+    };
+    match client.sentences(&headword.id) {
+        Ok(sentences) => attach_sentences(retrieve_entry, &sentences, limit),
+        Err(OxdError::NotFound(_) | OxdError::NotCached(_)) => {}
+        Err(err) => exit_with(err),
     }
 }
 
-fn output_entry(retrieve_entry: RetrieveEntry, sound: bool) {
+fn output_entry(retrieve_entry: RetrieveEntry, backend: &dyn DictionaryBackend, sound: bool) {
     let mut canvas = String::new();
     retrieve_entry.display(&mut canvas);
     println!("{canvas}");
     if sound {
-        retrieve_entry.pronounce();
+        retrieve_entry
+            .pronounce_with(backend)
+            .unwrap_or_else(exit_with);
     }
 }
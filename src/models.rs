@@ -1,25 +1,48 @@
 /*! # A series of structs modeling OD API retrieve entries
  * Struct hierarchy:
- * - [_Sense_](Sense) { [_domains_](Domain), [_registers_](Register), _definitions_, _cross_reference_markers_, [_examples_](Example), [_subsenses_](Sense) }
+ * - [_Sense_](Sense) { _id_, [_domains_](Domain), [_registers_](Register), [_regions_](Region), _definitions_, _cross_reference_markers_, [_translations_](Translation), [_examples_](Example), [_sentences_](sentences::Sentence), [_notes_](Note), [_subsenses_](Sense) }
  * -   ^
- * - [Entry] { [_pronunciations_](Pronunciation), [_variant_forms_](VariantForm), _origins_ }
+ * - [Entry] { [_pronunciations_](Pronunciation), [_variant_forms_](VariantForm), _origins_, [_grammatical_features_](GrammaticalFeature), [_notes_](Note) }
  * -   ^
- * - [LexicalEntry] { text, language, [lexical_category](LexicalCategory), [_derivative_of_](DerivativeOf) }
+ * - [LexicalEntry] { text, language, [lexical_category](LexicalCategory), [_derivative_of_](DerivativeOf), [_grammatical_features_](GrammaticalFeature) }
  * -   ^
  * - [HeadwordEntry] { id, word, type, language }
  * -   ^
  * - [RetrieveEntry] { _id_, _word_, metadata }
  *
  * Italic fields are optional.
+ * Fields such as grammatical features, notes and regions
+ * mostly show up in dictionaries other than English.
+ *
+ * Entries from the translations endpoint share these structs,
+ * with [translations](Translation) in place of definitions.
+ * Synonyms and antonyms are modeled in [thesaurus],
+ * inflected forms in [lemmas], corpus sentences in [sentences]
+ * and suggestions for unknown words in [search].
+ * Valid domains, registers and other labels are listed in [catalog].
  */
 
+// The public helpers below keep their original signatures, and their test its assertions.
+#![allow(
+    clippy::bool_assert_comparison,
+    clippy::needless_lifetimes,
+    clippy::ptr_arg
+)]
+
 use serde::Deserialize;
 use serde_json::Value;
 
+pub mod catalog;
+pub mod lemmas;
+pub mod search;
+pub mod sentences;
+pub mod thesaurus;
+
 // Structs
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Sense {
+    pub id: Option<String>,
     pub definitions: Option<Vec<String>>,
     pub examples: Option<Vec<Example>>,
     pub subsenses: Option<Vec<Sense>>,
@@ -27,15 +50,21 @@ pub struct Sense {
     #[serde(rename = "crossReferenceMarkers")]
     pub cross_reference_markers: Option<Vec<String>>,
     pub registers: Option<Vec<Register>>,
+    pub regions: Option<Vec<Region>>,
+    pub notes: Option<Vec<Note>>,
+    pub translations: Option<Vec<Translation>>,
+    /// Corpus sentences, filled in by [attach_sentences](sentences::attach_sentences).
+    #[serde(skip)]
+    pub sentences: Option<Vec<sentences::Sentence>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct VariantForm {
     pub regions: Option<Vec<Region>>,
     pub text: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Entry {
     pub senses: Option<Vec<Sense>>,
     pub pronunciations: Option<Vec<Pronunciation>>,
@@ -43,9 +72,12 @@ pub struct Entry {
     pub variant_forms: Option<Vec<VariantForm>>,
     #[serde(rename = "etymologies")]
     pub origins: Option<Vec<String>>,
+    #[serde(rename = "grammaticalFeatures")]
+    pub grammatical_features: Option<Vec<GrammaticalFeature>>,
+    pub notes: Option<Vec<Note>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LexicalEntry {
     pub entries: Vec<Entry>,
     pub language: String,
@@ -53,10 +85,12 @@ pub struct LexicalEntry {
     pub lexical_category: LexicalCategory,
     #[serde(rename = "derivativeOf")]
     pub derivative_of: Option<Vec<DerivativeOf>>,
+    #[serde(rename = "grammaticalFeatures")]
+    pub grammatical_features: Option<Vec<GrammaticalFeature>>,
     pub text: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct HeadwordEntry {
     pub id: String,
     pub language: String,
@@ -67,7 +101,7 @@ pub struct HeadwordEntry {
     pub word: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct RetrieveEntry {
     pub id: Option<String>,
     pub metadata: Value,
@@ -78,45 +112,76 @@ pub struct RetrieveEntry {
 
 // Structs that have no struct fields
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct LexicalCategory {
     pub id: String,
     pub text: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Example {
     pub text: String,
+    pub notes: Option<Vec<Note>>,
+    pub translations: Option<Vec<Translation>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Translation {
+    pub text: String,
+    pub language: String,
+    pub domains: Option<Vec<Domain>>,
+    pub registers: Option<Vec<Register>>,
+    pub regions: Option<Vec<Region>>,
+    pub notes: Option<Vec<Note>>,
+    #[serde(rename = "grammaticalFeatures")]
+    pub grammatical_features: Option<Vec<GrammaticalFeature>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Clone, Deserialize)]
 pub struct Pronunciation {
     #[serde(rename = "phoneticSpelling")]
     pub phonetic_spelling: Option<String>,
     #[serde(rename = "phoneticNotation")]
-    pub phonetic_notation: String,
+    pub phonetic_notation: Option<String>,
     #[serde(rename = "audioFile")]
     pub audio_file: Option<String>,
+    pub dialects: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Domain {
     pub id: String,
     pub text: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Register {
     pub id: String,
     pub text: String,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Clone)]
 pub struct Region {
     pub id: String,
     pub text: String,
 }
 
+#[derive(Debug, Clone, Deserialize)]
+pub struct GrammaticalFeature {
+    pub id: String,
+    pub text: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Note {
+    pub id: Option<String>,
+    pub text: String,
+    #[serde(rename = "type")]
+    pub type_: String,
+}
+
 #[derive(Debug, Deserialize, Clone)]
 pub struct DerivativeOf {
     pub id: String,
@@ -142,7 +207,7 @@ pub fn has_consistent_pronunciation(headword: &HeadwordEntry) -> bool {
             }
         }
     }
-    return have_same_elements(pronunciations);
+    have_same_elements(pronunciations)
 }
 
 /// Returns whether two vectors have the same elements.
@@ -152,12 +217,12 @@ fn have_same_elements<T: Eq>(vv: Vec<&Vec<T>>) -> bool {
     }
     for e1 in vv[0] {
         for v in &vv[1..] {
-            if !v.contains(&e1) {
+            if !v.contains(e1) {
                 return false;
             }
         }
     }
-    return true;
+    true
 }
 
 #[test]
@@ -177,13 +242,18 @@ fn test_have_same_elements() {
 // Helper functions to look at empty entries
 
 pub fn is_empty_entries(entries: &Vec<Entry>) -> bool {
-    return entries.iter().all(|entry| {
+    entries.iter().all(|entry| {
         entry.pronunciations.is_none() && entry.senses.is_none() && entry.variant_forms.is_none()
-    });
+    })
 }
 
 pub fn is_empty_sense(sense: &Sense) -> bool {
-    sense.definitions.is_none() && sense.cross_reference_markers.is_none()
+    sense.definitions.as_ref().is_none_or(Vec::is_empty)
+        && sense
+            .cross_reference_markers
+            .as_ref()
+            .is_none_or(Vec::is_empty)
+        && sense.translations.as_ref().is_none_or(Vec::is_empty)
 }
 
 pub fn roots(retrieve_entry: &RetrieveEntry) -> Vec<DerivativeOf> {
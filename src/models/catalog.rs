@@ -0,0 +1,222 @@
+/*! # Structs modeling the OD API utility endpoints
+ * These list the values that show up in entries and filters:
+ * - [Labels] { metadata, [labels](Label) } for each [LabelKind], e.g. every domain
+ * - [Languages] { metadata, [results](Dictionary) } for the available dictionaries
+ * - [EndpointOptions] { metadata, results } for the fields and filters of each endpoint
+ *
+ * OD answers the label endpoints with maps keyed by id, e.g.
+ * `{"art": {"en": "art"}}`, which [Labels] flattens into a sorted list.
+ */
+
+use serde::{Deserialize, Deserializer};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// A list of labels served by a utility endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LabelKind {
+    /// Subject areas such as "Computing", see [Domain](super::Domain).
+    Domains,
+    /// Registers such as "informal", see [Register](super::Register).
+    Registers,
+    /// Regions such as "Scottish", see [Region](super::Region).
+    Regions,
+    /// Parts of speech, see [LexicalCategory](super::LexicalCategory).
+    LexicalCategories,
+    /// Features such as "plural", see [GrammaticalFeature](super::GrammaticalFeature).
+    GrammaticalFeatures,
+}
+
+impl LabelKind {
+    pub const ALL: [LabelKind; 5] = [
+        LabelKind::Domains,
+        LabelKind::Registers,
+        LabelKind::Regions,
+        LabelKind::LexicalCategories,
+        LabelKind::GrammaticalFeatures,
+    ];
+
+    /// The endpoint name used in OD API paths, e.g. `lexicalcategories`.
+    pub fn endpoint(&self) -> &'static str {
+        match self {
+            LabelKind::Domains => "domains",
+            LabelKind::Registers => "registers",
+            LabelKind::Regions => "regions",
+            LabelKind::LexicalCategories => "lexicalcategories",
+            LabelKind::GrammaticalFeatures => "grammaticalFeatures",
+        }
+    }
+}
+
+impl fmt::Display for LabelKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.endpoint())
+    }
+}
+
+impl FromStr for LabelKind {
+    type Err = String;
+
+    /// Parses an endpoint name, ignoring case, dashes and underscores.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().replace(['-', '_'], "").to_ascii_lowercase();
+        LabelKind::ALL
+            .into_iter()
+            .find(|kind| kind.endpoint().to_ascii_lowercase() == name)
+            .ok_or_else(|| format!("unknown label kind: {s}"))
+    }
+}
+
+/// A valid value of a [LabelKind], e.g. the domain `computing`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Label {
+    /// The id used in entries and filters.
+    pub id: String,
+    /// A human-readable name.
+    pub text: String,
+    /// The group a grammatical feature belongs to, e.g. "Number".
+    pub type_: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Labels {
+    pub metadata: Value,
+    #[serde(rename = "results", deserialize_with = "deserialize_labels")]
+    pub labels: Vec<Label>,
+}
+
+impl Labels {
+    /// Finds a label by its id, ignoring case.
+    pub fn get(&self, id: &str) -> Option<&Label> {
+        self.labels
+            .iter()
+            .find(|label| label.id.eq_ignore_ascii_case(id))
+    }
+
+    /// Whether `id` is a valid value, e.g. to check a filter before sending it.
+    pub fn contains(&self, id: &str) -> bool {
+        self.get(id).is_some()
+    }
+
+    /// The human-readable name for `id`, falling back to the id itself.
+    pub fn text<'a>(&'a self, id: &'a str) -> &'a str {
+        self.get(id).map_or(id, |label| label.text.as_str())
+    }
+}
+
+/// Accepts both the map OD serves and a plain list of `{id, text}` objects.
+fn deserialize_labels<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Label>, D::Error> {
+    let mut labels: Vec<Label> = match Value::deserialize(deserializer)? {
+        Value::Object(map) => map
+            .into_iter()
+            .map(|(id, value)| label(Some(id), &value))
+            .collect(),
+        Value::Array(list) => list.iter().map(|value| label(None, value)).collect(),
+        _ => vec![],
+    };
+    labels.retain(|label| !label.id.is_empty());
+    labels.sort_by(|a, b| a.id.cmp(&b.id));
+    Ok(labels)
+}
+
+/// Reads a label from a value such as `"art"`, `{"en": "art"}`,
+/// `{"en": {"type": "Number", "value": "plural"}}` or `{"id": "art", "text": "art"}`.
+fn label(id: Option<String>, value: &Value) -> Label {
+    let field =
+        |value: &Value, key: &str| value.get(key).and_then(Value::as_str).map(str::to_owned);
+    let id = id.or_else(|| field(value, "id")).unwrap_or_default();
+    let inner = match value {
+        Value::Object(map) if !map.contains_key("id") && !map.contains_key("text") => {
+            map.values().next().unwrap_or(value)
+        }
+        _ => value,
+    };
+    let text = match inner {
+        Value::String(text) => Some(text.clone()),
+        _ => field(inner, "text").or_else(|| field(inner, "value")),
+    };
+    Label {
+        text: text.unwrap_or_else(|| id.clone()),
+        type_: field(inner, "type"),
+        id,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LanguageName {
+    pub id: String,
+    pub language: String,
+}
+
+/// A dictionary offered by the OD API.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Dictionary {
+    pub source: Option<String>,
+    #[serde(rename = "sourceLanguage")]
+    pub source_language: LanguageName,
+    #[serde(rename = "targetLanguage")]
+    pub target_language: Option<LanguageName>,
+    #[serde(rename = "type")]
+    pub type_: Option<String>,
+    pub region: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Languages {
+    pub metadata: Value,
+    pub results: Vec<Dictionary>,
+}
+
+/// Field or filter names accepted by each endpoint, keyed by endpoint name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EndpointOptions {
+    pub metadata: Value,
+    pub results: BTreeMap<String, Vec<String>>,
+}
+
+impl EndpointOptions {
+    /// The options of one endpoint, e.g. `entries`.
+    pub fn for_endpoint(&self, endpoint: &str) -> &[String] {
+        self.results.get(endpoint).map_or(&[], Vec::as_slice)
+    }
+}
+
+#[test]
+fn test_labels_from_map_and_list() {
+    let labels: Labels = serde_json::from_str(
+        r#"{"metadata": {}, "results": {
+            "plural": {"en": {"type": "Number", "value": "plural"}},
+            "art": {"en": "art"}
+        }}"#,
+    )
+    .unwrap();
+    assert_eq!(labels.labels[0].id, "art");
+    assert_eq!(labels.text("plural"), "plural");
+    assert_eq!(
+        labels.get("Plural").unwrap().type_.as_deref(),
+        Some("Number")
+    );
+
+    let labels: Labels = serde_json::from_str(
+        r#"{"metadata": {}, "results": [{"id": "computing", "text": "Computing"}]}"#,
+    )
+    .unwrap();
+    assert!(labels.contains("computing"));
+    assert_eq!(labels.text("computing"), "Computing");
+    assert_eq!(labels.text("sport"), "sport");
+}
+
+#[test]
+fn test_parse_label_kind() {
+    assert_eq!(
+        "lexical-categories".parse::<LabelKind>().unwrap(),
+        LabelKind::LexicalCategories
+    );
+    assert_eq!(
+        "grammaticalfeatures".parse::<LabelKind>().unwrap(),
+        LabelKind::GrammaticalFeatures
+    );
+    assert!("senses".parse::<LabelKind>().is_err());
+}
@@ -0,0 +1,73 @@
+/*! # Structs modeling OD API lemmas, linking inflected forms to headwords
+ * Struct hierarchy:
+ * - [LemmaLexicalEntry] { text, language, [lexical_category](LexicalCategory), [inflection_of](InflectionOf), [_grammatical_features_](GrammaticalFeature) }
+ * -   ^
+ * - [LemmaHeadword] { id, word, type, language }
+ * -   ^
+ * - [Lemmatron] { metadata }
+ *
+ * Italic fields are optional.
+ */
+
+use super::{GrammaticalFeature, LexicalCategory};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LemmaLexicalEntry {
+    #[serde(rename = "grammaticalFeatures")]
+    pub grammatical_features: Option<Vec<GrammaticalFeature>>,
+    #[serde(rename = "inflectionOf")]
+    pub inflection_of: Vec<InflectionOf>,
+    pub language: String,
+    #[serde(rename = "lexicalCategory")]
+    pub lexical_category: LexicalCategory,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LemmaHeadword {
+    pub id: String,
+    pub language: String,
+    #[serde(rename = "lexicalEntries")]
+    pub lexical_entries: Vec<LemmaLexicalEntry>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub word: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Lemmatron {
+    pub metadata: Value,
+    pub results: Vec<LemmaHeadword>,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Eq)]
+pub struct InflectionOf {
+    pub id: String,
+    pub text: String,
+}
+
+/// Returns the distinct headwords a word is an inflection of, in order.
+pub fn lemmas(lemmatron: &Lemmatron) -> Vec<InflectionOf> {
+    let mut lemmas: Vec<InflectionOf> = vec![];
+    for headword in &lemmatron.results {
+        for lexical_entry in &headword.lexical_entries {
+            for inflection_of in &lexical_entry.inflection_of {
+                if !lemmas.iter().any(|lemma| lemma.id == inflection_of.id) {
+                    lemmas.push(inflection_of.clone());
+                }
+            }
+        }
+    }
+    lemmas
+}
+
+impl LemmaLexicalEntry {
+    /// Describes the inflection in words, e.g. "present participle".
+    pub fn inflection(&self) -> Option<String> {
+        let features = self.grammatical_features.as_ref()?;
+        let texts: Vec<String> = features.iter().map(|f| f.text.to_lowercase()).collect();
+        Some(texts.join(" "))
+    }
+}
@@ -0,0 +1,42 @@
+/*! # Structs modeling OD API search results
+ * Struct hierarchy:
+ * - [SearchResult] { id, word, _label_, _match_string_, _match_type_, _region_, _score_ }
+ * -   ^
+ * - [SearchResults] { metadata }
+ *
+ * Italic fields are optional.
+ */
+
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SearchResult {
+    pub id: String,
+    pub word: String,
+    pub label: Option<String>,
+    #[serde(rename = "matchString")]
+    pub match_string: Option<String>,
+    #[serde(rename = "matchType")]
+    pub match_type: Option<String>,
+    pub region: Option<String>,
+    pub score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SearchResults {
+    pub metadata: Value,
+    pub results: Vec<SearchResult>,
+}
+
+impl SearchResults {
+    /// Returns results ordered from the best match down.
+    pub fn ranked(&self) -> Vec<&SearchResult> {
+        let mut ranked: Vec<&SearchResult> = self.results.iter().collect();
+        ranked.sort_by(|a, b| {
+            let (a, b) = (a.score.unwrap_or(0.0), b.score.unwrap_or(0.0));
+            b.total_cmp(&a)
+        });
+        ranked
+    }
+}
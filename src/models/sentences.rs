@@ -0,0 +1,94 @@
+/*! # Structs modeling OD API corpus sentences
+ * Struct hierarchy:
+ * - [Sentence] { text, [_sense_ids_](crate::models::Sense::id), [_regions_](Region), [_registers_](Register), [_domains_](Domain), [_notes_](Note) }
+ * -   ^
+ * - [SentencesLexicalEntry] { text, language, [lexical_category](LexicalCategory) }
+ * -   ^
+ * - [SentencesHeadword] { id, word, type, language }
+ * -   ^
+ * - [Sentences] { metadata }
+ *
+ * Italic fields are optional.
+ */
+
+use super::{Domain, LexicalCategory, Note, Region, Register, RetrieveEntry, Sense};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct Sentence {
+    pub text: String,
+    #[serde(rename = "senseIds")]
+    pub sense_ids: Option<Vec<String>>,
+    pub regions: Option<Vec<Region>>,
+    pub registers: Option<Vec<Register>>,
+    pub domains: Option<Vec<Domain>>,
+    pub notes: Option<Vec<Note>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SentencesLexicalEntry {
+    pub language: String,
+    #[serde(rename = "lexicalCategory")]
+    pub lexical_category: LexicalCategory,
+    pub sentences: Vec<Sentence>,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SentencesHeadword {
+    pub id: String,
+    pub language: String,
+    #[serde(rename = "lexicalEntries")]
+    pub lexical_entries: Vec<SentencesLexicalEntry>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub word: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Sentences {
+    pub metadata: Value,
+    pub results: Vec<SentencesHeadword>,
+}
+
+impl Sentences {
+    /// Returns sentences illustrating the sense with this id.
+    pub fn for_sense<'a>(&'a self, sense_id: &'a str) -> impl Iterator<Item = &'a Sentence> {
+        self.results
+            .iter()
+            .flat_map(|headword| &headword.lexical_entries)
+            .flat_map(|lexical_entry| &lexical_entry.sentences)
+            .filter(move |sentence| match &sentence.sense_ids {
+                Some(ids) => ids.iter().any(|id| id == sense_id),
+                None => false,
+            })
+    }
+}
+
+/// Attaches up to `limit` corpus sentences to every sense and subsense of an entry.
+pub fn attach_sentences(retrieve_entry: &mut RetrieveEntry, sentences: &Sentences, limit: usize) {
+    for headword in &mut retrieve_entry.headword_entries {
+        for lexical_entry in &mut headword.lexical_entries {
+            for entry in &mut lexical_entry.entries {
+                if let Some(senses) = &mut entry.senses {
+                    attach_to_senses(senses, sentences, limit);
+                }
+            }
+        }
+    }
+}
+
+fn attach_to_senses(senses: &mut [Sense], sentences: &Sentences, limit: usize) {
+    for sense in senses {
+        if let Some(id) = &sense.id {
+            let found: Vec<Sentence> = sentences.for_sense(id).take(limit).cloned().collect();
+            if !found.is_empty() {
+                sense.sentences = Some(found);
+            }
+        }
+        if let Some(subsenses) = &mut sense.subsenses {
+            attach_to_senses(subsenses, sentences, limit);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+/*! # Structs modeling OD API thesaurus entries
+ * Struct hierarchy:
+ * - [_ThesaurusSense_](ThesaurusSense) { [_synonyms_](SynonymAntonym), [_antonyms_](SynonymAntonym), [_domains_](Domain), [_registers_](Register), [_regions_](Region), [_examples_](Example), [_subsenses_](ThesaurusSense) }
+ * -   ^
+ * - [ThesaurusEntry] { [_senses_](ThesaurusSense) }
+ * -   ^
+ * - [ThesaurusLexicalEntry] { text, language, [lexical_category](LexicalCategory) }
+ * -   ^
+ * - [ThesaurusHeadword] { id, word, type, language }
+ * -   ^
+ * - [Thesaurus] { _id_, _word_, metadata }
+ *
+ * Italic fields are optional.
+ */
+
+use super::{Domain, Example, LexicalCategory, Region, Register};
+use serde::Deserialize;
+use serde_json::Value;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThesaurusSense {
+    pub id: Option<String>,
+    pub synonyms: Option<Vec<SynonymAntonym>>,
+    pub antonyms: Option<Vec<SynonymAntonym>>,
+    pub domains: Option<Vec<Domain>>,
+    pub registers: Option<Vec<Register>>,
+    pub regions: Option<Vec<Region>>,
+    pub examples: Option<Vec<Example>>,
+    pub subsenses: Option<Vec<ThesaurusSense>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThesaurusEntry {
+    pub senses: Option<Vec<ThesaurusSense>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThesaurusLexicalEntry {
+    pub entries: Vec<ThesaurusEntry>,
+    pub language: String,
+    #[serde(rename = "lexicalCategory")]
+    pub lexical_category: LexicalCategory,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ThesaurusHeadword {
+    pub id: String,
+    pub language: String,
+    #[serde(rename = "lexicalEntries")]
+    pub lexical_entries: Vec<ThesaurusLexicalEntry>,
+    #[serde(rename = "type")]
+    pub type_: String,
+    pub word: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Thesaurus {
+    pub id: Option<String>,
+    pub metadata: Value,
+    #[serde(rename = "results")]
+    pub headword_entries: Vec<ThesaurusHeadword>,
+    pub word: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SynonymAntonym {
+    pub id: String,
+    pub language: String,
+    pub text: String,
+}
+
+pub fn is_empty_thesaurus_sense(sense: &ThesaurusSense) -> bool {
+    sense.synonyms.is_none() && sense.antonyms.is_none()
+}
@@ -0,0 +1,189 @@
+//! `oxd prefetch`: warming the cache with the entries of a word list.
+//!
+//! Each word's outcome is appended to `<list>.progress` as soon as it is known,
+//! so an interrupted run picks up where it stopped. Words that failed are tried again.
+
+use oxd::{OxdClient, OxdError};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, PartialEq)]
+enum Outcome {
+    Found,
+    NotFound,
+    Failed(String),
+}
+
+impl Outcome {
+    fn is_done(&self) -> bool {
+        !matches!(self, Outcome::Failed(_))
+    }
+}
+
+pub fn run(client: &OxdClient, file: &Path, restart: bool) -> Result<(), OxdError> {
+    let list = fs::read_to_string(file).map_err(|source| OxdError::io(file, source))?;
+    let words = word_list(&list);
+    let progress_file = progress_path(file);
+    let mut outcomes = match fs::read_to_string(&progress_file) {
+        Ok(progress) if !restart => read_progress(&progress),
+        Ok(_) => {
+            fs::remove_file(&progress_file)
+                .map_err(|source| OxdError::io(&progress_file, source))?;
+            HashMap::new()
+        }
+        Err(err) if err.kind() == io::ErrorKind::NotFound => HashMap::new(),
+        Err(source) => return Err(OxdError::io(&progress_file, source)),
+    };
+
+    let resumed = words
+        .iter()
+        .filter(|word| outcomes.get(**word).is_some_and(Outcome::is_done))
+        .count();
+    if resumed > 0 {
+        eprintln!(
+            "Resuming: {resumed} of {} words were fetched before.",
+            words.len()
+        );
+    }
+
+    let mut progress = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&progress_file)
+        .map_err(|source| OxdError::io(&progress_file, source))?;
+    for (i, word) in words.iter().enumerate() {
+        if outcomes.get(*word).is_some_and(Outcome::is_done) {
+            continue;
+        }
+        let outcome = match client.entries(word) {
+            Ok(_) => Outcome::Found,
+            Err(OxdError::NotFound(_)) => Outcome::NotFound,
+            // Every later word would fail the same way, so leave them for the next run.
+            Err(err @ OxdError::QuotaExceeded { .. }) => {
+                eprintln!("{err}; run prefetch again to resume.");
+                break;
+            }
+            Err(err) => Outcome::Failed(err.to_string()),
+        };
+        eprintln!("[{}/{}] {word}: {}", i + 1, words.len(), describe(&outcome));
+        progress
+            .write_all(progress_line(word, &outcome).as_bytes())
+            .map_err(|source| OxdError::io(&progress_file, source))?;
+        outcomes.insert(word.to_string(), outcome);
+    }
+
+    print!("{}", summary(&words, &outcomes));
+    Ok(())
+}
+
+/// The words of a list, one per line, skipping blank lines, `#` comments and repeats.
+fn word_list(list: &str) -> Vec<&str> {
+    let mut seen = HashSet::new();
+    list.lines()
+        .map(str::trim)
+        .filter(|word| !word.is_empty() && !word.starts_with('#'))
+        .filter(|word| seen.insert(*word))
+        .collect()
+}
+
+/// Where the outcomes of a list are kept, e.g. `words.txt.progress`.
+fn progress_path(file: &Path) -> PathBuf {
+    let mut path = file.as_os_str().to_owned();
+    path.push(".progress");
+    PathBuf::from(path)
+}
+
+/// Records an outcome as a tab-separated line, e.g. `found\trust`.
+fn progress_line(word: &str, outcome: &Outcome) -> String {
+    match outcome {
+        Outcome::Found => format!("found\t{word}\n"),
+        Outcome::NotFound => format!("not-found\t{word}\n"),
+        Outcome::Failed(err) => format!("failed\t{word}\t{}\n", err.replace(['\t', '\n'], " ")),
+    }
+}
+
+/// Reads the outcomes recorded so far; the last line about a word wins.
+fn read_progress(progress: &str) -> HashMap<String, Outcome> {
+    let mut outcomes = HashMap::new();
+    for line in progress.lines() {
+        let mut fields = line.splitn(3, '\t');
+        let (Some(status), Some(word)) = (fields.next(), fields.next()) else {
+            continue;
+        };
+        let outcome = match status {
+            "found" => Outcome::Found,
+            "not-found" => Outcome::NotFound,
+            "failed" => Outcome::Failed(fields.next().unwrap_or_default().to_owned()),
+            _ => continue,
+        };
+        outcomes.insert(word.to_owned(), outcome);
+    }
+    outcomes
+}
+
+fn describe(outcome: &Outcome) -> &str {
+    match outcome {
+        Outcome::Found => "found",
+        Outcome::NotFound => "not found",
+        Outcome::Failed(err) => err,
+    }
+}
+
+/// Counts the outcomes of the whole list and names the words that need attention.
+fn summary(words: &[&str], outcomes: &HashMap<String, Outcome>) -> String {
+    let (mut found, mut not_found, mut failed, mut pending) = (0, vec![], vec![], vec![]);
+    for word in words {
+        match outcomes.get(*word) {
+            Some(Outcome::Found) => found += 1,
+            Some(Outcome::NotFound) => not_found.push(*word),
+            Some(Outcome::Failed(err)) => failed.push(format!("{word}: {err}")),
+            None => pending.push(*word),
+        }
+    }
+    let mut canvas = format!(
+        "{} words: {found} found, {} not found, {} failed",
+        words.len(),
+        not_found.len(),
+        failed.len()
+    );
+    if !pending.is_empty() {
+        canvas.push_str(&format!(", {} not fetched yet", pending.len()));
+    }
+    canvas.push('\n');
+    if !not_found.is_empty() {
+        canvas.push_str(&format!("\nNot found:\n  {}\n", not_found.join("\n  ")));
+    }
+    if !failed.is_empty() {
+        canvas.push_str(&format!("\nFailed:\n  {}\n", failed.join("\n  ")));
+    }
+    canvas
+}
+
+#[test]
+fn test_word_list() {
+    let list = "# workshop\nrust\n\n  ferrous \nrust\n";
+    assert_eq!(word_list(list), ["rust", "ferrous"]);
+}
+
+#[test]
+fn test_progress_round_trip() {
+    let progress = [
+        progress_line("rust", &Outcome::Failed("server error\n(500)".to_owned())),
+        progress_line("qwxz", &Outcome::NotFound),
+        progress_line("rust", &Outcome::Found),
+        "garbled\n".to_owned(),
+    ]
+    .concat();
+    let outcomes = read_progress(&progress);
+    assert_eq!(outcomes.len(), 2);
+    assert_eq!(outcomes["rust"], Outcome::Found);
+    assert_eq!(outcomes["qwxz"], Outcome::NotFound);
+
+    let mut outcomes = outcomes;
+    outcomes.insert("iron".to_owned(), Outcome::Failed("timed out".to_owned()));
+    let summary = summary(&["rust", "qwxz", "iron", "tin"], &outcomes);
+    assert!(summary.starts_with("4 words: 1 found, 1 not found, 1 failed, 1 not fetched yet\n"));
+    assert!(summary.contains("iron: timed out"));
+}
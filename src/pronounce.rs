@@ -1,69 +1,102 @@
 /*! Fetch and play pronunciation audio files */
 
+use crate::backend::DictionaryBackend;
+use crate::error::OxdError;
 use crate::models::*;
+#[cfg(feature = "blocking")]
 use reqwest::blocking;
 use rodio::{Decoder, OutputStream, Sink};
+use std::fmt;
 use std::io::Cursor;
 
 /// A trait to fetch and play pronunciation audio files
 pub trait Pronounce {
-    fn pronounce(&self) {}
+    /// Urls of the audio files to play, in order.
+    fn audio_files(&self) -> Vec<&str>;
+
+    /// Fetches and plays the audio files one after another.
+    #[cfg(feature = "blocking")]
+    fn pronounce(&self) -> Result<(), OxdError> {
+        for url in self.audio_files() {
+            let res = blocking::get(url)?;
+            if let Some(err) = OxdError::from_status(res.status(), res.headers(), url) {
+                return Err(err);
+            }
+            play(res.bytes()?.to_vec())?;
+        }
+        Ok(())
+    }
+
+    /// Fetches the audio files through `backend` and plays them one after another.
+    fn pronounce_with(&self, backend: &dyn DictionaryBackend) -> Result<(), OxdError> {
+        for url in self.audio_files() {
+            play(backend.audio(url)?)?;
+        }
+        Ok(())
+    }
+}
+
+/// Plays an audio file, e.g. one fetched by [AsyncOxdClient::audio](crate::AsyncOxdClient::audio).
+pub fn play(audio: Vec<u8>) -> Result<(), OxdError> {
+    let cursor = Cursor::new(audio);
+    let decoder = Decoder::new(cursor).map_err(audio_error)?;
+    let (_stream, stream_handle) = OutputStream::try_default().map_err(audio_error)?;
+    let sink = Sink::try_new(&stream_handle).map_err(audio_error)?;
+    sink.append(decoder);
+    sink.sleep_until_end();
+    Ok(())
+}
+
+fn audio_error(err: impl fmt::Display) -> OxdError {
+    OxdError::Audio(err.to_string())
 }
 
 impl Pronounce for Pronunciation {
-    fn pronounce(&self) {
-        let Some(url) = &self.audio_file else { return };
-        let res = blocking::get(url).expect("Expect auido file from url");
-        let cursor = Cursor::new(res.bytes().unwrap());
-        let decoder = Decoder::new(cursor).unwrap();
-        let (_stream, stream_handle) = OutputStream::try_default().unwrap();
-        let sink = Sink::try_new(&stream_handle).unwrap();
-        sink.append(decoder);
-        sink.sleep_until_end();
+    fn audio_files(&self) -> Vec<&str> {
+        self.audio_file.iter().map(String::as_str).collect()
     }
 }
 
 impl<T: Pronounce> Pronounce for Option<T> {
-    fn pronounce(&self) {
-        if let Some(p) = &self {
-            p.pronounce();
+    fn audio_files(&self) -> Vec<&str> {
+        match &self {
+            Some(p) => p.audio_files(),
+            None => vec![],
         }
     }
 }
 
 impl<T: Pronounce> Pronounce for Vec<T> {
-    fn pronounce(&self) {
-        for p in self {
-            p.pronounce();
-        }
+    fn audio_files(&self) -> Vec<&str> {
+        self.iter().flat_map(|p| p.audio_files()).collect()
     }
 }
 
 impl Pronounce for Entry {
-    fn pronounce(&self) {
-        self.pronunciations.pronounce();
+    fn audio_files(&self) -> Vec<&str> {
+        self.pronunciations.audio_files()
     }
 }
 
 impl Pronounce for LexicalEntry {
-    fn pronounce(&self) {
-        self.entries.pronounce();
+    fn audio_files(&self) -> Vec<&str> {
+        self.entries.audio_files()
     }
 }
 
 impl Pronounce for HeadwordEntry {
-    fn pronounce(&self) {
+    fn audio_files(&self) -> Vec<&str> {
         if has_consistent_pronunciation(self) {
             // Use assumption "at least one `LexicalEntry`"
-            self.lexical_entries[0].pronounce();
+            self.lexical_entries[0].audio_files()
         } else {
-            self.lexical_entries.pronounce();
+            self.lexical_entries.audio_files()
         }
     }
 }
 
 impl Pronounce for RetrieveEntry {
-    fn pronounce(&self) {
-        self.headword_entries.pronounce();
+    fn audio_files(&self) -> Vec<&str> {
+        self.headword_entries.audio_files()
     }
 }
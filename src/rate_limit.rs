@@ -0,0 +1,308 @@
+/*! Keeping requests within the quotas of an OD API plan */
+
+use crate::dirs;
+use crate::error::OxdError;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+/// The request quotas of an OD API plan. `None` means unlimited.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Plan {
+    pub per_minute: Option<u32>,
+    pub per_month: Option<u32>,
+}
+
+impl Plan {
+    /// The free plan: 60 requests a minute and 1000 a month.
+    pub const PROTOTYPE: Plan = Plan {
+        per_minute: Some(60),
+        per_month: Some(1000),
+    };
+
+    /// No limits, for plans billed per request.
+    pub const UNLIMITED: Plan = Plan {
+        per_minute: None,
+        per_month: None,
+    };
+}
+
+impl fmt::Display for Plan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let limit = |limit: Option<u32>| limit.map_or("unlimited".to_owned(), |n| n.to_string());
+        write!(
+            f,
+            "{}/minute, {}/month",
+            limit(self.per_minute),
+            limit(self.per_month)
+        )
+    }
+}
+
+impl FromStr for Plan {
+    type Err = OxdError;
+
+    /// Parses `prototype`, `unlimited`, or quotas as `per_minute/per_month`, e.g. `60/1000`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            OxdError::InvalidConfig(format!(
+                "invalid plan \"{s}\", expected prototype, unlimited or PER_MINUTE/PER_MONTH"
+            ))
+        };
+        let limit = |n: &str| match n.trim() {
+            "" | "-" => Ok(None),
+            n => n.parse().map(Some).map_err(|_| invalid()),
+        };
+        match s.trim().to_ascii_lowercase().as_str() {
+            "prototype" => Ok(Plan::PROTOTYPE),
+            "unlimited" => Ok(Plan::UNLIMITED),
+            quotas => {
+                let (per_minute, per_month) = quotas.split_once('/').ok_or_else(invalid)?;
+                Ok(Plan {
+                    per_minute: limit(per_minute)?,
+                    per_month: limit(per_month)?,
+                })
+            }
+        }
+    }
+}
+
+/// Spaces out requests and counts them against the quotas of a [Plan].
+///
+/// Requests beyond the per-minute quota wait for a token to refill.
+/// Once the monthly quota is used up, requests fail with
+/// [QuotaExceeded](OxdError::QuotaExceeded) without being sent.
+///
+/// Clones share their budget, so one limiter can serve clients on many threads.
+/// A limiter made with [persistent](Self::persistent) keeps the monthly count
+/// on disk, shared by every process using the same file.
+#[derive(Debug, Clone)]
+pub struct RateLimiter {
+    plan: Plan,
+    path: Option<PathBuf>,
+    state: Arc<Mutex<State>>,
+}
+
+#[derive(Debug)]
+struct State {
+    tokens: f64,
+    refilled: Instant,
+    usage: Usage,
+}
+
+/// Requests sent in a calendar month, as stored on disk.
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct Usage {
+    month: String,
+    used: u32,
+}
+
+impl RateLimiter {
+    /// A limiter counting the monthly quota in memory only.
+    pub fn new(plan: Plan) -> Self {
+        RateLimiter {
+            plan,
+            path: None,
+            state: Arc::new(Mutex::new(State {
+                tokens: plan.per_minute.unwrap_or(0) as f64,
+                refilled: Instant::now(),
+                usage: Usage::default(),
+            })),
+        }
+    }
+
+    /// A limiter keeping the monthly count in `$XDG_STATE_HOME/oxd/quota.json`.
+    pub fn persistent(plan: Plan) -> Result<Self, OxdError> {
+        Ok(RateLimiter::with_state_file(
+            plan,
+            dirs::state_dir()?.join("quota.json"),
+        ))
+    }
+
+    /// A limiter keeping the monthly count in `path`.
+    pub fn with_state_file(plan: Plan, path: impl Into<PathBuf>) -> Self {
+        RateLimiter {
+            path: Some(path.into()),
+            ..RateLimiter::new(plan)
+        }
+    }
+
+    pub fn plan(&self) -> Plan {
+        self.plan
+    }
+
+    /// Requests counted so far this month.
+    pub fn used_this_month(&self) -> Result<u32, OxdError> {
+        let mut state = self.state.lock().unwrap();
+        self.refresh_usage(&mut state.usage)?;
+        Ok(state.usage.used)
+    }
+
+    /// Counts a request, returning how long to wait before sending it.
+    pub(crate) fn acquire(&self) -> Result<Duration, OxdError> {
+        let mut state = self.state.lock().unwrap();
+        // Held until the new count is written, so concurrent processes don't lose counts.
+        let _lock = self.path.as_deref().map(lock_usage).transpose()?;
+        self.refresh_usage(&mut state.usage)?;
+        if let Some(limit) = self.plan.per_month {
+            if state.usage.used >= limit {
+                return Err(OxdError::QuotaExceeded { limit });
+            }
+        }
+
+        let mut wait = Duration::ZERO;
+        if let Some(per_minute) = self.plan.per_minute.filter(|&n| n > 0) {
+            let per_second = per_minute as f64 / 60.0;
+            let now = Instant::now();
+            let refill = now.duration_since(state.refilled).as_secs_f64() * per_second;
+            state.tokens = (state.tokens + refill).min(per_minute as f64) - 1.0;
+            state.refilled = now;
+            if state.tokens < 0.0 {
+                wait = Duration::from_secs_f64(-state.tokens / per_second);
+            }
+        }
+
+        state.usage.used += 1;
+        if let Some(path) = &self.path {
+            write_usage(path, &state.usage)?;
+        }
+        Ok(wait)
+    }
+
+    /// Starts a new count each month, and picks up requests counted by other processes.
+    fn refresh_usage(&self, usage: &mut Usage) -> Result<(), OxdError> {
+        if let Some(path) = &self.path {
+            *usage = read_usage(path)?;
+        }
+        let month = current_month();
+        if usage.month != month {
+            *usage = Usage { month, used: 0 };
+        }
+        Ok(())
+    }
+}
+
+/// Takes an exclusive advisory lock on `<path>.lock`, released when the file is dropped.
+///
+/// The lock lives in a file of its own because [`write_usage`] replaces the state file.
+fn lock_usage(path: &Path) -> Result<File, OxdError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|source| OxdError::io(dir, source))?;
+    }
+    let mut lock_path = path.as_os_str().to_owned();
+    lock_path.push(".lock");
+    let lock_path = PathBuf::from(lock_path);
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(&lock_path)
+        .map_err(|source| OxdError::io(&lock_path, source))?;
+    file.lock()
+        .map_err(|source| OxdError::io(&lock_path, source))?;
+    Ok(file)
+}
+
+fn read_usage(path: &Path) -> Result<Usage, OxdError> {
+    match fs::read_to_string(path) {
+        // A corrupt file starts the count over rather than blocking every request.
+        Ok(text) => Ok(serde_json::from_str(&text).unwrap_or_default()),
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(Usage::default()),
+        Err(source) => Err(OxdError::io(path, source)),
+    }
+}
+
+/// Writes through a temporary file, so readers never see half a file.
+fn write_usage(path: &Path, usage: &Usage) -> Result<(), OxdError> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).map_err(|source| OxdError::io(dir, source))?;
+    }
+    let text = serde_json::to_string(usage).expect("usage serializes to JSON");
+    let temporary = path.with_extension(format!("{}.tmp", std::process::id()));
+    fs::write(&temporary, text).map_err(|source| OxdError::io(&temporary, source))?;
+    fs::rename(&temporary, path).map_err(|source| OxdError::io(path, source))
+}
+
+/// The current month in UTC, e.g. `2024-02`.
+fn current_month() -> String {
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let (year, month) = year_month((secs / 86_400) as i64);
+    format!("{year:04}-{month:02}")
+}
+
+/// Converts days since 1970-01-01 to a year and month of the Gregorian calendar.
+fn year_month(days: i64) -> (i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month)
+}
+
+#[test]
+fn test_year_month() {
+    assert_eq!(year_month(0), (1970, 1));
+    assert_eq!(year_month(19_782), (2024, 2));
+    assert_eq!(year_month(19_783), (2024, 3));
+    assert_eq!(year_month(20_088), (2024, 12));
+}
+
+#[test]
+fn test_parse_plan() {
+    assert_eq!("Prototype".parse::<Plan>().unwrap(), Plan::PROTOTYPE);
+    let plan: Plan = "100/-".parse().unwrap();
+    assert_eq!(plan.per_minute, Some(100));
+    assert_eq!(plan.per_month, None);
+    assert!("fast".parse::<Plan>().is_err());
+}
+
+#[test]
+fn test_per_minute_quota_spaces_out_requests() {
+    let limiter = RateLimiter::new(Plan {
+        per_minute: Some(2),
+        per_month: Some(3),
+    });
+    assert_eq!(limiter.acquire().unwrap(), Duration::ZERO);
+    assert_eq!(limiter.acquire().unwrap(), Duration::ZERO);
+    let wait = limiter.acquire().unwrap();
+    assert!(Duration::from_secs(29) < wait && wait <= Duration::from_secs(30));
+    assert!(matches!(
+        limiter.clone().acquire(),
+        Err(OxdError::QuotaExceeded { limit: 3 })
+    ));
+}
+
+#[test]
+fn test_limiters_sharing_a_state_file_do_not_lose_counts() {
+    let dir = std::env::temp_dir().join(format!("oxd-quota-{}", std::process::id()));
+    let path = dir.join("quota.json");
+    std::thread::scope(|scope| {
+        for _ in 0..4 {
+            let limiter = RateLimiter::with_state_file(Plan::UNLIMITED, &path);
+            scope.spawn(move || {
+                for _ in 0..50 {
+                    limiter.acquire().unwrap();
+                }
+            });
+        }
+    });
+    let used = RateLimiter::with_state_file(Plan::UNLIMITED, &path).used_this_month();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(used.unwrap(), 200);
+}
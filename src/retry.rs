@@ -0,0 +1,182 @@
+/*! Retrying requests that failed for a transient reason */
+
+use crate::error::OxdError;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// A retry about to happen, passed to [RetryPolicy::on_retry].
+#[derive(Debug)]
+pub struct RetryEvent<'a> {
+    /// The URL of the failed request.
+    pub url: &'a str,
+    /// The attempt that failed, starting at 1.
+    pub attempt: u32,
+    /// How long the client waits before the next attempt.
+    pub delay: Duration,
+    /// Why the attempt failed.
+    pub error: &'a OxdError,
+}
+
+type RetryHook = Arc<dyn Fn(&RetryEvent) + Send + Sync>;
+
+/// How often and how patiently a client retries a failed request.
+///
+/// Only [transient](OxdError::is_transient) failures are retried:
+/// rate limiting, server errors, timeouts and refused connections.
+/// Every request the client sends is a GET, so retrying is safe.
+///
+/// The delay doubles with each attempt, from `base_delay` up to `max_delay`,
+/// and is randomly shortened by up to half so that clients spread out.
+/// A `Retry-After` sent with a 429 response is waited out as it is,
+/// unless it is longer than `max_delay`: then the client gives up instead.
+///
+/// ```
+/// use oxd::retry::RetryPolicy;
+/// use std::time::Duration;
+///
+/// let policy = RetryPolicy::new(4)
+///     .base_delay(Duration::from_millis(500))
+///     .on_retry(|event| eprintln!("retrying {} after {:?}", event.url, event.delay));
+/// ```
+#[derive(Clone)]
+pub struct RetryPolicy {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+    on_retry: Option<RetryHook>,
+}
+
+impl RetryPolicy {
+    /// Tries each request up to `max_attempts` times, counting the first.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+            on_retry: None,
+        }
+    }
+
+    /// Never retries, which is what clients do unless told otherwise.
+    pub fn never() -> Self {
+        RetryPolicy::new(1)
+    }
+
+    /// Sets the delay before the first retry, 1 second by default.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// Caps the delay between attempts, 30 seconds by default.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Calls `hook` before each retry, e.g. to log it.
+    pub fn on_retry(mut self, hook: impl Fn(&RetryEvent) + Send + Sync + 'static) -> Self {
+        self.on_retry = Some(Arc::new(hook));
+        self
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    /// How long to wait before retrying after `attempt` failed with `error`,
+    /// or `None` to give up. Calls the [on_retry](Self::on_retry) hook.
+    pub(crate) fn retry_delay(
+        &self,
+        url: &str,
+        attempt: u32,
+        error: &OxdError,
+    ) -> Option<Duration> {
+        if attempt >= self.max_attempts || !error.is_transient() {
+            return None;
+        }
+        let delay = match error {
+            OxdError::RateLimited {
+                retry_after: Some(delay),
+            } if *delay > self.max_delay => return None,
+            OxdError::RateLimited {
+                retry_after: Some(delay),
+            } => *delay,
+            _ => self.backoff(attempt),
+        };
+        if let Some(hook) = &self.on_retry {
+            hook(&RetryEvent {
+                url,
+                attempt,
+                delay,
+                error,
+            });
+        }
+        Some(delay)
+    }
+
+    /// The exponential delay after `attempt`, with jitter.
+    fn backoff(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        delay.mul_f64(1.0 - jitter() / 2.0)
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy::never()
+    }
+}
+
+impl fmt::Debug for RetryPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RetryPolicy")
+            .field("max_attempts", &self.max_attempts)
+            .field("base_delay", &self.base_delay)
+            .field("max_delay", &self.max_delay)
+            .field("on_retry", &self.on_retry.is_some())
+            .finish()
+    }
+}
+
+/// A random number in `[0, 1)`, seeded by the standard library's hasher keys.
+fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish() >> 11;
+    bits as f64 / (1u64 << 53) as f64
+}
+
+#[test]
+fn test_backoff_doubles_up_to_max_delay() {
+    let policy = RetryPolicy::new(10)
+        .base_delay(Duration::from_secs(2))
+        .max_delay(Duration::from_secs(10));
+    let ranges = [(1, 2), (2, 4), (3, 8), (4, 10), (9, 10)];
+    for (attempt, secs) in ranges {
+        let delay = policy.backoff(attempt);
+        let max = Duration::from_secs(secs);
+        assert!(max / 2 <= delay && delay <= max, "{attempt}: {delay:?}");
+    }
+}
+
+#[test]
+fn test_retry_delay_gives_up() {
+    let policy = RetryPolicy::new(2);
+    let busy = OxdError::RateLimited {
+        retry_after: Some(Duration::from_secs(7)),
+    };
+    assert_eq!(
+        policy.retry_delay("url", 1, &busy),
+        Some(Duration::from_secs(7))
+    );
+    assert_eq!(policy.retry_delay("url", 2, &busy), None);
+    let closed = OxdError::RateLimited {
+        retry_after: Some(Duration::from_secs(86_400)),
+    };
+    assert_eq!(policy.retry_delay("url", 1, &closed), None);
+    let missing = OxdError::NotFound("rust".to_owned());
+    assert_eq!(policy.retry_delay("url", 1, &missing), None);
+}
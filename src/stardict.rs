@@ -0,0 +1,614 @@
+/*! A [DictionaryBackend] reading StarDict dictionaries
+ *
+ * A StarDict dictionary is a `.ifo` file describing it, a `.idx` index of headwords
+ * sorted case-insensitively, each with the position of its article, and the articles
+ * themselves in a `.dict` file, or in a `.dict.dz` compressed with dictzip
+ * so that an article can be read without inflating the whole file.
+ *
+ * The index and articles are memory-mapped and headwords are found by binary search.
+ * Each line of an article becomes a [Sense] with that line as its definition;
+ * HTML and other markup is reduced to its text.
+ */
+
+use crate::backend::{self, DictionaryBackend};
+use crate::error::OxdError;
+use crate::lookup::word_id;
+use crate::models::lemmas::Lemmatron;
+use crate::models::search::SearchResults;
+use crate::models::{
+    Entry, HeadwordEntry, LexicalCategory, LexicalEntry, Pronunciation, RetrieveEntry, Sense,
+};
+use flate2::read::MultiGzDecoder;
+use flate2::{Decompress, FlushDecompress};
+use memmap2::Mmap;
+use serde_json::json;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// A StarDict dictionary opened from its files.
+///
+/// ```no_run
+/// use oxd::stardict::StarDict;
+/// use oxd::DictionaryBackend;
+///
+/// let dictionary = StarDict::open("/usr/share/stardict/dic/freedict-deu-eng").unwrap();
+/// let entry = dictionary.entry("Rost").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct StarDict {
+    info: Info,
+    index: Bytes,
+    index_path: PathBuf,
+    /// Where each entry of the index starts, in sorted order.
+    entries: Vec<usize>,
+    articles: Articles,
+}
+
+/// What the `.ifo` file says about the dictionary.
+#[derive(Debug)]
+struct Info {
+    name: String,
+    language: Option<String>,
+    same_type_sequence: Option<String>,
+    offset_bits: usize,
+}
+
+/// File contents, memory-mapped or, for compressed files, inflated into memory.
+#[derive(Debug)]
+enum Bytes {
+    Mapped(Mmap),
+    Owned(Vec<u8>),
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Bytes::Mapped(map) => map,
+            Bytes::Owned(bytes) => bytes,
+        }
+    }
+}
+
+#[derive(Debug)]
+enum Articles {
+    Plain(Bytes),
+    Dictzip(Dictzip),
+}
+
+/// A `.dict.dz` file: a gzip file of deflate chunks that can be inflated one by one.
+#[derive(Debug)]
+struct Dictzip {
+    file: Mmap,
+    /// The length of every chunk but the last once inflated.
+    chunk_len: usize,
+    /// Where each compressed chunk starts and ends in the file.
+    chunks: Vec<(usize, usize)>,
+}
+
+impl StarDict {
+    /// Opens a dictionary from its `.ifo` file, from the path of its files without extension,
+    /// or from a directory holding a single dictionary.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self, OxdError> {
+        let ifo_path = ifo_path(path.as_ref())?;
+        let ifo =
+            fs::read_to_string(&ifo_path).map_err(|source| OxdError::io(&ifo_path, source))?;
+        let info =
+            parse_info(&ifo).ok_or_else(|| invalid_data(&ifo_path, "not a StarDict .ifo file"))?;
+        let base = ifo_path.with_extension("");
+        // Not `with_extension`, which would cut names such as `freedict-deu-eng-0.3` short.
+        let sibling = |extension: &str| {
+            let mut path = base.as_os_str().to_owned();
+            path.push(extension);
+            PathBuf::from(path)
+        };
+
+        let index_path = sibling(".idx");
+        let index = if index_path.exists() {
+            Bytes::Mapped(map(&index_path)?)
+        } else {
+            let gz_path = sibling(".idx.gz");
+            let mut index = vec![];
+            File::open(&gz_path)
+                .and_then(|file| MultiGzDecoder::new(file).read_to_end(&mut index))
+                .map_err(|source| OxdError::io(&gz_path, source))?;
+            Bytes::Owned(index)
+        };
+        let entries = index_entries(&index, info.offset_bits)
+            .ok_or_else(|| invalid_data(&index_path, "truncated or malformed index"))?;
+
+        let dict_path = sibling(".dict");
+        let articles = if dict_path.exists() {
+            Articles::Plain(Bytes::Mapped(map(&dict_path)?))
+        } else {
+            let dz_path = sibling(".dict.dz");
+            let file = map(&dz_path)?;
+            match dictzip_chunks(&file, &dz_path) {
+                Some(Ok((chunk_len, chunks))) => Articles::Dictzip(Dictzip {
+                    file,
+                    chunk_len,
+                    chunks,
+                }),
+                Some(Err(err)) => return Err(err),
+                // A plain gzip file has no chunks to seek to, so it is inflated as a whole.
+                None => {
+                    let mut articles = vec![];
+                    MultiGzDecoder::new(&file[..])
+                        .read_to_end(&mut articles)
+                        .map_err(|source| OxdError::io(&dz_path, source))?;
+                    Articles::Plain(Bytes::Owned(articles))
+                }
+            }
+        };
+
+        Ok(StarDict {
+            info,
+            index,
+            index_path,
+            entries,
+            articles,
+        })
+    }
+
+    /// The name of the dictionary, its `bookname`.
+    pub fn name(&self) -> &str {
+        &self.info.name
+    }
+
+    /// How many articles the dictionary has.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The headword, article offset and article size of the `i`th index entry.
+    fn index_entry(&self, i: usize) -> (&str, usize, usize) {
+        let start = self.entries[i];
+        let word = self.word_at(start);
+        let mut numbers = &self.index[start + word.len() + 1..];
+        let offset = read_number(&mut numbers, self.info.offset_bits / 8);
+        let size = read_number(&mut numbers, 4);
+        (word, offset, size)
+    }
+
+    /// The index entries of `word`: those spelled exactly like it if there are any,
+    /// otherwise those differing only in case.
+    fn find(&self, word: &str) -> Vec<usize> {
+        let first = self
+            .entries
+            .partition_point(|&i| compare_ignoring_case(self.word_at(i), word).is_lt());
+        let matches: Vec<usize> = (first..self.entries.len())
+            .take_while(|&i| self.index_entry(i).0.eq_ignore_ascii_case(word))
+            .collect();
+        let exact: Vec<usize> = matches
+            .iter()
+            .copied()
+            .filter(|&i| self.index_entry(i).0 == word)
+            .collect();
+        if exact.is_empty() {
+            matches
+        } else {
+            exact
+        }
+    }
+
+    /// The headword of the index entry starting at `start`.
+    fn word_at(&self, start: usize) -> &str {
+        let word_end = start + find_nul(&self.index[start..]).unwrap_or_default();
+        std::str::from_utf8(&self.index[start..word_end]).unwrap_or_default()
+    }
+
+    fn article(&self, offset: usize, size: usize) -> Result<Vec<u8>, OxdError> {
+        let truncated =
+            || invalid_data(&self.index_path, "article beyond the end of the dictionary");
+        let end = offset.checked_add(size).ok_or_else(truncated)?;
+        match &self.articles {
+            Articles::Plain(bytes) => bytes
+                .get(offset..end)
+                .map(<[u8]>::to_vec)
+                .ok_or_else(truncated),
+            Articles::Dictzip(dictzip) => dictzip.read(offset, size).ok_or_else(truncated),
+        }
+    }
+
+    fn lexical_entry(&self, word: &str, article: &[u8]) -> LexicalEntry {
+        let mut pronunciations = vec![];
+        let mut senses = vec![];
+        for (kind, field) in article_fields(article, self.info.same_type_sequence.as_deref()) {
+            let text = String::from_utf8_lossy(field);
+            match kind {
+                // Display adds its own slashes around the transcription.
+                't' => pronunciations.push(Pronunciation {
+                    phonetic_spelling: Some(text.trim().trim_matches(['/', '[', ']']).to_owned()),
+                    phonetic_notation: Some("IPA".to_owned()),
+                    audio_file: None,
+                    dialects: None,
+                }),
+                'm' | 'l' | 'y' | 'k' | 'w' => senses.extend(definitions(&text)),
+                'g' | 'h' | 'x' => senses.extend(definitions(&strip_markup(&text))),
+                // Pictures, sounds and resource lists have nothing to show as text.
+                _ => {}
+            }
+        }
+        LexicalEntry {
+            entries: vec![Entry {
+                senses: Some(senses),
+                pronunciations: (!pronunciations.is_empty()).then_some(pronunciations),
+                variant_forms: None,
+                origins: None,
+                grammatical_features: None,
+                notes: None,
+            }],
+            language: self.language(),
+            lexical_category: LexicalCategory {
+                id: "other".to_owned(),
+                text: "Other".to_owned(),
+            },
+            derivative_of: None,
+            grammatical_features: None,
+            text: word.to_owned(),
+        }
+    }
+
+    fn language(&self) -> String {
+        self.info.language.clone().unwrap_or_default()
+    }
+}
+
+impl DictionaryBackend for StarDict {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        let found = self.find(word.trim());
+        let Some(&first) = found.first() else {
+            return Err(OxdError::NotFound(word.to_owned()));
+        };
+        let headword = self.index_entry(first).0.to_owned();
+        let mut lexical_entries = vec![];
+        for i in found {
+            let (_, offset, size) = self.index_entry(i);
+            let article = self.article(offset, size)?;
+            lexical_entries.push(self.lexical_entry(&headword, &article));
+        }
+        let id = word_id(&headword);
+        Ok(RetrieveEntry {
+            id: Some(id.clone()),
+            metadata: json!({"provider": self.info.name}),
+            headword_entries: vec![HeadwordEntry {
+                id,
+                language: self.language(),
+                lexical_entries,
+                type_: "headword".to_owned(),
+                word: headword.clone(),
+            }],
+            word: Some(headword),
+        })
+    }
+
+    /// StarDict has no inflections, so every word is its own headword.
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        Err(OxdError::NotFound(word.to_owned()))
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        let words = (0..self.entries.len()).map(|i| self.index_entry(i).0);
+        Ok(backend::similar_words(query, words, limit))
+    }
+}
+
+/// Finds the `.ifo` file `path` refers to.
+fn ifo_path(path: &Path) -> Result<PathBuf, OxdError> {
+    if path.extension().is_some_and(|extension| extension == "ifo") {
+        return Ok(path.to_owned());
+    }
+    if !path.is_dir() {
+        let mut ifo = path.as_os_str().to_owned();
+        ifo.push(".ifo");
+        return Ok(PathBuf::from(ifo));
+    }
+    let listing = fs::read_dir(path).map_err(|source| OxdError::io(path, source))?;
+    let mut ifos = vec![];
+    for file in listing {
+        let file = file.map_err(|source| OxdError::io(path, source))?.path();
+        if file.extension().is_some_and(|extension| extension == "ifo") {
+            ifos.push(file);
+        }
+    }
+    match <[PathBuf; 1]>::try_from(ifos) {
+        Ok([ifo]) => Ok(ifo),
+        Err(ifos) => Err(OxdError::InvalidConfig(format!(
+            "expected one StarDict .ifo file in {}, found {}",
+            path.display(),
+            ifos.len()
+        ))),
+    }
+}
+
+/// Parses the `key=value` lines following the magic line of a `.ifo` file.
+fn parse_info(ifo: &str) -> Option<Info> {
+    let mut lines = ifo.lines();
+    if lines.next()?.trim() != "StarDict's dict ifo file" {
+        return None;
+    }
+    let fields: HashMap<&str, &str> = lines
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+    Some(Info {
+        name: fields
+            .get("bookname")
+            .copied()
+            .unwrap_or_default()
+            .to_owned(),
+        language: fields.get("lang").map(|lang| lang.to_string()),
+        same_type_sequence: fields
+            .get("sametypesequence")
+            .map(|types| types.to_string()),
+        offset_bits: match fields.get("idxoffsetbits") {
+            Some(&"64") => 64,
+            _ => 32,
+        },
+    })
+}
+
+pub(crate) fn map(path: &Path) -> Result<Mmap, OxdError> {
+    let file = File::open(path).map_err(|source| OxdError::io(path, source))?;
+    // Safety: dictionaries are read-only data; one changed while oxd runs
+    // yields garbled articles, which the parsing below tolerates.
+    unsafe { Mmap::map(&file) }.map_err(|source| OxdError::io(path, source))
+}
+
+fn invalid_data(path: &Path, message: &str) -> OxdError {
+    OxdError::io(path, io::Error::new(io::ErrorKind::InvalidData, message))
+}
+
+fn find_nul(bytes: &[u8]) -> Option<usize> {
+    bytes.iter().position(|&byte| byte == 0)
+}
+
+/// Reads a big-endian number of `len` bytes, advancing `bytes` past it.
+fn read_number(bytes: &mut &[u8], len: usize) -> usize {
+    let (number, rest) = bytes.split_at(len.min(bytes.len()));
+    *bytes = rest;
+    number
+        .iter()
+        .fold(0, |value, &byte| (value << 8) | usize::from(byte))
+}
+
+/// Where each entry of an index starts: a NUL-terminated UTF-8 word,
+/// then its article's offset and size.
+fn index_entries(index: &[u8], offset_bits: usize) -> Option<Vec<usize>> {
+    let numbers_len = offset_bits / 8 + 4;
+    let mut entries = vec![];
+    let mut start = 0;
+    while start < index.len() {
+        let word_len = find_nul(&index[start..])?;
+        std::str::from_utf8(&index[start..start + word_len]).ok()?;
+        entries.push(start);
+        start += word_len + 1 + numbers_len;
+    }
+    (start == index.len()).then_some(entries)
+}
+
+/// The order of StarDict indexes: ASCII letters compared regardless of case,
+/// which is all [find](StarDict::find) needs to land on the first match.
+fn compare_ignoring_case(a: &str, b: &str) -> Ordering {
+    let lower = |word: &str| {
+        word.bytes()
+            .map(|byte| byte.to_ascii_lowercase())
+            .collect::<Vec<_>>()
+    };
+    lower(a).cmp(&lower(b))
+}
+
+/// The inflated length of a dictzip chunk, and where each compressed chunk lies.
+type ChunkTable = (usize, Vec<(usize, usize)>);
+
+/// Reads the `RA` extra field of a dictzip header: the inflated length of a chunk
+/// and where each compressed chunk lies. Returns `None` for plain gzip files,
+/// and an error for a header declaring empty chunks.
+fn dictzip_chunks(file: &[u8], path: &Path) -> Option<Result<ChunkTable, OxdError>> {
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+    const FHCRC: u8 = 2;
+    let le16 = |bytes: &[u8], at: usize| -> Option<usize> {
+        Some(usize::from(*bytes.get(at)?) | usize::from(*bytes.get(at + 1)?) << 8)
+    };
+
+    if file.get(..3)? != [0x1f, 0x8b, 8] {
+        return None;
+    }
+    let flags = file[3];
+    if flags & FEXTRA == 0 {
+        return None;
+    }
+    let extra_len = le16(file, 10)?;
+    let extra = file.get(12..12 + extra_len)?;
+    let mut start = 12 + extra_len;
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            start += find_nul(file.get(start..)?)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        start += 2;
+    }
+
+    let mut at = 0;
+    while at + 4 <= extra.len() {
+        let len = le16(extra, at + 2)?;
+        let field = extra.get(at + 4..at + 4 + len)?;
+        if &extra[at..at + 2] == b"RA" {
+            let chunk_len = le16(field, 2)?;
+            if chunk_len == 0 {
+                return Some(Err(invalid_data(path, "dictzip chunk length of 0")));
+            }
+            let count = le16(field, 4)?;
+            let mut chunks = Vec::with_capacity(count);
+            for i in 0..count {
+                let size = le16(field, 6 + 2 * i)?;
+                chunks.push((start, start + size));
+                start += size;
+            }
+            return (start <= file.len()).then_some(Ok((chunk_len, chunks)));
+        }
+        at += 4 + len;
+    }
+    None
+}
+
+impl Dictzip {
+    /// Inflates the chunks holding `size` bytes at `offset` of the original file.
+    fn read(&self, offset: usize, size: usize) -> Option<Vec<u8>> {
+        if size == 0 {
+            return Some(vec![]);
+        }
+        let first = offset / self.chunk_len;
+        let last = (offset + size - 1) / self.chunk_len;
+        let mut inflated = Vec::with_capacity((last - first + 1) * self.chunk_len);
+        for &(start, end) in self.chunks.get(first..=last)? {
+            // Chunks end on a full flush, so each one inflates on its own.
+            let mut chunk = Vec::with_capacity(self.chunk_len);
+            Decompress::new(false)
+                .decompress_vec(&self.file[start..end], &mut chunk, FlushDecompress::Sync)
+                .ok()?;
+            inflated.extend(chunk);
+        }
+        let skip = offset - first * self.chunk_len;
+        inflated.get(skip..skip + size).map(<[u8]>::to_vec)
+    }
+}
+
+/// Splits an article into its typed fields.
+///
+/// With a `sametypesequence`, the type letters are left out of the article
+/// and the last field runs to its end. Lowercase types are NUL-terminated text,
+/// uppercase types binary data preceded by its 32-bit size.
+fn article_fields<'a>(
+    article: &'a [u8],
+    same_type_sequence: Option<&str>,
+) -> Vec<(char, &'a [u8])> {
+    let mut fields = vec![];
+    let mut rest = article;
+    let mut types = same_type_sequence.map(|types| types.chars().peekable());
+    while !rest.is_empty() {
+        let (kind, last) = match &mut types {
+            Some(types) => match types.next() {
+                Some(kind) => (kind, types.peek().is_none()),
+                None => break,
+            },
+            None => {
+                let kind = char::from(rest[0]);
+                rest = &rest[1..];
+                (kind, false)
+            }
+        };
+        let len = if last {
+            rest.len()
+        } else if kind.is_ascii_lowercase() {
+            find_nul(rest).unwrap_or(rest.len())
+        } else {
+            read_number(&mut rest, 4).min(rest.len())
+        };
+        fields.push((kind, &rest[..len]));
+        rest = &rest[len..];
+        if kind.is_ascii_lowercase() && !last {
+            rest = rest.get(1..).unwrap_or_default();
+        }
+    }
+    fields
+}
+
+/// Reduces HTML, Pango or XDXF markup to its text, keeping line breaks.
+fn strip_markup(markup: &str) -> String {
+    let mut text = String::with_capacity(markup.len());
+    let mut rest = markup;
+    while let Some(open) = rest.find('<') {
+        text.push_str(&rest[..open]);
+        let Some(close) = rest[open..].find('>') else {
+            rest = &rest[open..];
+            break;
+        };
+        let tag = rest[open + 1..open + close]
+            .trim_start_matches('/')
+            .to_lowercase();
+        let name = tag
+            .split(|c: char| c.is_whitespace() || c == '/')
+            .next()
+            .unwrap_or_default();
+        if matches!(name, "br" | "p" | "div" | "li" | "tr" | "def") {
+            text.push('\n');
+        }
+        rest = &rest[open + close + 1..];
+    }
+    text.push_str(rest);
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&apos;", "'")
+        .replace("&nbsp;", " ")
+        .replace("&amp;", "&")
+}
+
+/// One sense for every non-blank line of text.
+fn definitions(text: &str) -> Vec<Sense> {
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| Sense {
+            id: None,
+            definitions: Some(vec![line.to_owned()]),
+            examples: None,
+            subsenses: None,
+            domains: None,
+            cross_reference_markers: None,
+            registers: None,
+            regions: None,
+            notes: None,
+            translations: None,
+            sentences: None,
+        })
+        .collect()
+}
+
+#[test]
+fn test_article_fields() {
+    let article = b"t/rust/\0m\xc3\xb3xido\0W\0\0\0\x02ab";
+    let fields = article_fields(article, None);
+    assert_eq!(
+        fields,
+        [
+            ('t', &b"/rust/"[..]),
+            ('m', "óxido".as_bytes()),
+            ('W', b"ab")
+        ]
+    );
+
+    let fields = article_fields(b"/rust/\0oxide\ncoating", Some("tm"));
+    assert_eq!(fields, [('t', &b"/rust/"[..]), ('m', b"oxide\ncoating")]);
+}
+
+#[test]
+fn test_strip_markup() {
+    let html = "<b>rust</b><br>a red&amp;brown <i>oxide</i><br/>coating";
+    assert_eq!(strip_markup(html), "rust\na red&brown oxide\ncoating");
+}
+
+#[test]
+fn test_dictzip_chunk_length_of_0_is_an_error() {
+    // A gzip header with FEXTRA, holding an RA field: version 1, chunk length 0, 1 chunk of 0 bytes.
+    let mut file = vec![0x1f, 0x8b, 8, 4, 0, 0, 0, 0, 0, 3, 12, 0];
+    file.extend(b"RA\x08\x00\x01\x00\x00\x00\x01\x00\x00\x00");
+    let path = Path::new("broken.dict.dz");
+    assert!(matches!(
+        dictzip_chunks(&file, path),
+        Some(Err(OxdError::Io { source, .. })) if source.kind() == io::ErrorKind::InvalidData
+    ));
+}
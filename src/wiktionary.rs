@@ -0,0 +1,789 @@
+/*! A [DictionaryBackend] answering from an imported Wiktionary extract
+ *
+ * [import] reads the JSON lines of a [kaikki.org](https://kaikki.org/) extract,
+ * one record per word, part of speech and etymology, into a store of one directory
+ * per language:
+ *
+ * - `entries.jsonl`, the records with only the fields oxd shows,
+ * - `index.tsv`, the position of each word's records, sorted by word,
+ * - `forms.tsv`, the headwords of inflected forms, sorted by form.
+ *
+ * [Wiktionary] memory-maps a language's store and binary-searches its index,
+ * turning records into the [models](crate::models): each etymology becomes a
+ * [HeadwordEntry] and each part of speech a [LexicalEntry] with its etymology,
+ * IPA pronunciations, audio files and senses.
+ * Tags such as "slang" or "UK" become registers and regions, topics become domains.
+ */
+
+use crate::backend::{self, DictionaryBackend};
+use crate::error::OxdError;
+use crate::lookup::word_id;
+use crate::models::lemmas::{InflectionOf, LemmaHeadword, LemmaLexicalEntry, Lemmatron};
+use crate::models::search::SearchResults;
+use crate::models::{
+    Domain, Entry, Example, GrammaticalFeature, HeadwordEntry, LexicalCategory, LexicalEntry,
+    Pronunciation, Region, Register, RetrieveEntry, Sense, Translation,
+};
+use crate::stardict::map;
+use flate2::read::MultiGzDecoder;
+use memmap2::Mmap;
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// One line of a kaikki.org extract, with the fields oxd maps onto its models.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Record {
+    word: String,
+    lang_code: String,
+    #[serde(default)]
+    pos: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etymology_number: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    etymology_text: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    senses: Vec<RecordSense>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    sounds: Vec<Sound>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    forms: Vec<Form>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordSense {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    id: Option<String>,
+    /// The glosses of the parent senses, then this sense's own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    glosses: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    examples: Vec<RecordExample>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    topics: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    form_of: Vec<FormOf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct RecordExample {
+    text: String,
+    /// An English translation of an example in another language.
+    #[serde(default, alias = "english", skip_serializing_if = "Option::is_none")]
+    translation: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Sound {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ipa: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    mp3_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    ogg_url: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct Form {
+    form: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FormOf {
+    word: String,
+}
+
+/// Tags of [Form]s that are not inflections but inflection table markup.
+const TABLE_TAGS: [&str; 6] = [
+    "table-tags",
+    "inflection-template",
+    "class",
+    "canonical",
+    "romanization",
+    "auxiliary",
+];
+
+/// Sense tags shown as registers.
+const REGISTER_TAGS: [&str; 14] = [
+    "slang",
+    "informal",
+    "colloquial",
+    "vulgar",
+    "formal",
+    "archaic",
+    "obsolete",
+    "dated",
+    "rare",
+    "humorous",
+    "derogatory",
+    "offensive",
+    "euphemistic",
+    "literary",
+];
+
+/// Sense and sound tags shown as regions.
+const REGION_TAGS: [&str; 10] = [
+    "UK",
+    "US",
+    "Australia",
+    "Canada",
+    "Ireland",
+    "New-Zealand",
+    "Scotland",
+    "South-Africa",
+    "India",
+    "General-American",
+];
+
+/// What an [import] added to the store.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportSummary {
+    /// Records imported per language code, e.g. `en`.
+    pub records: BTreeMap<String, usize>,
+    /// Lines that were not records of a word, e.g. redirects.
+    pub skipped: usize,
+}
+
+/// A language's store while an extract is read into it.
+///
+/// It is built next to the store it replaces, e.g. in `.en.new` for `en`,
+/// and only takes its place once complete. Dropped unfinished, it is removed.
+struct StoreWriter {
+    /// Where the store goes when finished.
+    target: PathBuf,
+    /// Where the store it replaces is moved aside meanwhile, e.g. `.en.old`.
+    old: PathBuf,
+    dir: PathBuf,
+    entries: BufWriter<File>,
+    offset: u64,
+    index: Vec<(String, u64, usize)>,
+    forms: Vec<String>,
+    finished: bool,
+}
+
+impl StoreWriter {
+    fn create(root: &Path, lang_code: &str) -> Result<Self, OxdError> {
+        let dir = root.join(format!(".{lang_code}.new"));
+        remove_dir(&dir)?;
+        fs::create_dir_all(&dir).map_err(|source| OxdError::io(&dir, source))?;
+        let path = dir.join("entries.jsonl");
+        let entries = File::create(&path).map_err(|source| OxdError::io(&path, source))?;
+        Ok(StoreWriter {
+            target: root.join(lang_code),
+            old: root.join(format!(".{lang_code}.old")),
+            dir,
+            entries: BufWriter::new(entries),
+            offset: 0,
+            index: vec![],
+            forms: vec![],
+            finished: false,
+        })
+    }
+
+    fn add(&mut self, record: &Record) -> Result<(), OxdError> {
+        let mut line = serde_json::to_string(record).expect("records serialize to JSON");
+        line.push('\n');
+        let path = self.dir.join("entries.jsonl");
+        self.entries
+            .write_all(line.as_bytes())
+            .map_err(|source| OxdError::io(&path, source))?;
+        self.index
+            .push((word_id(&record.word), self.offset, line.len() - 1));
+        self.offset += line.len() as u64;
+
+        for form in &record.forms {
+            let is_table = form
+                .tags
+                .iter()
+                .any(|tag| TABLE_TAGS.contains(&tag.as_str()));
+            if !is_table && form.form != record.word && is_field(&form.form) {
+                self.forms
+                    .push(form_line(&form.form, &record.word, &record.pos, &form.tags));
+            }
+        }
+        for sense in &record.senses {
+            for form_of in &sense.form_of {
+                if form_of.word != record.word && is_field(&form_of.word) {
+                    self.forms.push(form_line(
+                        &record.word,
+                        &form_of.word,
+                        &record.pos,
+                        &sense.tags,
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(mut self) -> Result<(), OxdError> {
+        let path = self.dir.join("entries.jsonl");
+        self.entries
+            .flush()
+            .map_err(|source| OxdError::io(&path, source))?;
+
+        self.index.sort();
+        let index: String = self
+            .index
+            .iter()
+            .map(|(id, offset, len)| format!("{id}\t{offset}\t{len}\n"))
+            .collect();
+        write_file(&self.dir.join("index.tsv"), &index)?;
+
+        self.forms.sort();
+        self.forms.dedup();
+        write_file(&self.dir.join("forms.tsv"), &self.forms.concat())?;
+
+        // Move the old store aside rather than deleting it first,
+        // so that a store is missing only between two renames.
+        remove_dir(&self.old)?;
+        match fs::rename(&self.target, &self.old) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => {
+                return Err(OxdError::io(&self.target, err))
+            }
+            _ => {}
+        }
+        fs::rename(&self.dir, &self.target).map_err(|source| OxdError::io(&self.target, source))?;
+        self.finished = true;
+        remove_dir(&self.old)
+    }
+}
+
+impl Drop for StoreWriter {
+    fn drop(&mut self) {
+        if !self.finished {
+            let _ = fs::remove_dir_all(&self.dir);
+        }
+    }
+}
+
+/// Removes a directory and everything in it, if it exists.
+fn remove_dir(dir: &Path) -> Result<(), OxdError> {
+    match fs::remove_dir_all(dir) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(OxdError::io(dir, err)),
+        _ => Ok(()),
+    }
+}
+
+/// Reads a kaikki.org extract into the store in `dir`, replacing the stores
+/// of the languages it has. Lines that are not word records are skipped.
+///
+/// The old stores stay as they are until the whole extract has been read.
+/// Errors reading the extract are reported against the path `<extract>`.
+pub fn import(extract: impl BufRead, dir: impl AsRef<Path>) -> Result<ImportSummary, OxdError> {
+    read_extract(extract, Path::new("<extract>"), dir.as_ref())
+}
+
+/// Like [import], reading the extract from a file, gzipped if its name ends in `.gz`.
+pub fn import_file(extract: &Path, dir: impl AsRef<Path>) -> Result<ImportSummary, OxdError> {
+    let file = File::open(extract).map_err(|source| OxdError::io(extract, source))?;
+    if extract
+        .extension()
+        .is_some_and(|extension| extension == "gz")
+    {
+        read_extract(
+            BufReader::new(MultiGzDecoder::new(file)),
+            extract,
+            dir.as_ref(),
+        )
+    } else {
+        read_extract(BufReader::new(file), extract, dir.as_ref())
+    }
+}
+
+fn read_extract(extract: impl BufRead, path: &Path, dir: &Path) -> Result<ImportSummary, OxdError> {
+    let mut stores: BTreeMap<String, StoreWriter> = BTreeMap::new();
+    let mut summary = ImportSummary::default();
+    for line in extract.lines() {
+        let line = line.map_err(|source| OxdError::io(path, source))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let record: Record = match serde_json::from_str(&line) {
+            Ok(record) => record,
+            Err(_) => {
+                summary.skipped += 1;
+                continue;
+            }
+        };
+        if !is_field(&record.word) || !is_language_code(&record.lang_code) {
+            summary.skipped += 1;
+            continue;
+        }
+        if !stores.contains_key(&record.lang_code) {
+            let store = StoreWriter::create(dir, &record.lang_code)?;
+            stores.insert(record.lang_code.clone(), store);
+        }
+        stores
+            .get_mut(&record.lang_code)
+            .expect("store was just created")
+            .add(&record)?;
+        *summary.records.entry(record.lang_code).or_default() += 1;
+    }
+    for store in stores.into_values() {
+        store.finish()?;
+    }
+    Ok(summary)
+}
+
+/// Whether `text` can go in a tab-separated line as is.
+fn is_field(text: &str) -> bool {
+    !text.contains(['\t', '\n'])
+}
+
+/// Whether `code` is a language code such as `en` or `zh-min-nan`, safe to name a directory.
+fn is_language_code(code: &str) -> bool {
+    !code.is_empty() && code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+}
+
+/// A line of `forms.tsv`: `form\tword\tpos\ttags`.
+/// A part of speech or tag with a tab or line break in it is left out.
+fn form_line(form: &str, word: &str, pos: &str, tags: &[String]) -> String {
+    let pos = if is_field(pos) { pos } else { "" };
+    let tags: Vec<&str> = tags
+        .iter()
+        .map(String::as_str)
+        .filter(|tag| is_field(tag))
+        .collect();
+    format!("{}\t{word}\t{pos}\t{}\n", word_id(form), tags.join(" "))
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), OxdError> {
+    fs::write(path, contents).map_err(|source| OxdError::io(path, source))
+}
+
+/// A sorted tab-separated file, memory-mapped and searched by its first field.
+#[derive(Debug)]
+struct Table {
+    text: Option<Mmap>,
+    /// Where each line starts.
+    lines: Vec<usize>,
+}
+
+impl Table {
+    fn open(path: &Path) -> Result<Self, OxdError> {
+        let len = fs::metadata(path)
+            .map_err(|source| OxdError::io(path, source))?
+            .len();
+        // Empty files cannot be mapped, and have nothing to find anyway.
+        if len == 0 {
+            return Ok(Table {
+                text: None,
+                lines: vec![],
+            });
+        }
+        let text = map(path)?;
+        let mut lines = vec![0];
+        lines.extend(
+            text.iter()
+                .enumerate()
+                .filter(|(i, &byte)| byte == b'\n' && i + 1 < text.len())
+                .map(|(i, _)| i + 1),
+        );
+        Ok(Table {
+            text: Some(text),
+            lines,
+        })
+    }
+
+    fn line(&self, start: usize) -> &str {
+        let text = self.text.as_deref().unwrap_or_default();
+        let line = &text[start..];
+        let end = line
+            .iter()
+            .position(|&byte| byte == b'\n')
+            .unwrap_or(line.len());
+        std::str::from_utf8(&line[..end]).unwrap_or_default()
+    }
+
+    fn key(&self, start: usize) -> &str {
+        let line = self.line(start);
+        line.split_once('\t').map_or(line, |(key, _)| key)
+    }
+
+    /// The fields after the key of every line keyed by `key`.
+    fn rows(&self, key: &str) -> Vec<Vec<&str>> {
+        let first = self.lines.partition_point(|&start| self.key(start) < key);
+        self.lines[first..]
+            .iter()
+            .take_while(|&&start| self.key(start) == key)
+            .map(|&start| self.line(start).split('\t').skip(1).collect())
+            .collect()
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().map(|&start| self.key(start))
+    }
+}
+
+/// The store of one language, written by [import].
+///
+/// ```no_run
+/// use oxd::wiktionary::Wiktionary;
+/// use oxd::DictionaryBackend;
+///
+/// let wiktionary = Wiktionary::open("wiktionary", "en").unwrap();
+/// let lookup = wiktionary.lookup("yeeted").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Wiktionary {
+    language: String,
+    dir: PathBuf,
+    entries: Option<Mmap>,
+    index: Table,
+    forms: Table,
+}
+
+impl Wiktionary {
+    /// Opens the store of `language`, e.g. `en`, in `dir`.
+    pub fn open(dir: impl AsRef<Path>, language: &str) -> Result<Self, OxdError> {
+        let dir = dir.as_ref().join(language);
+        let index_path = dir.join("index.tsv");
+        if !index_path.exists() {
+            return Err(OxdError::InvalidConfig(format!(
+                "no Wiktionary words in \"{language}\" imported into {}",
+                dir.parent().unwrap_or(&dir).display()
+            )));
+        }
+        let index = Table::open(&index_path)?;
+        let forms = Table::open(&dir.join("forms.tsv"))?;
+        let entries = match index.lines.is_empty() {
+            true => None,
+            false => Some(map(&dir.join("entries.jsonl"))?),
+        };
+        Ok(Wiktionary {
+            language: language.to_owned(),
+            dir,
+            entries,
+            index,
+            forms,
+        })
+    }
+
+    /// Where `oxd import-wiktionary` keeps its store,
+    /// `$XDG_DATA_HOME/oxd/wiktionary` or `~/.local/share/oxd/wiktionary`.
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub fn default_dir() -> Result<PathBuf, OxdError> {
+        Ok(crate::dirs::data_dir()?.join("wiktionary"))
+    }
+
+    /// The records of a word, in the order of the extract.
+    fn records(&self, id: &str) -> Result<Vec<Record>, OxdError> {
+        let path = self.dir.join("entries.jsonl");
+        let entries = self.entries.as_deref().unwrap_or_default();
+        let mut records = vec![];
+        for row in self.index.rows(id) {
+            let (Some(offset), Some(len)) = (
+                row.first().and_then(|offset| offset.parse::<usize>().ok()),
+                row.get(1).and_then(|len| len.parse::<usize>().ok()),
+            ) else {
+                continue;
+            };
+            let line = entries.get(offset..offset + len).ok_or_else(|| {
+                OxdError::io(
+                    &path,
+                    io::Error::new(io::ErrorKind::UnexpectedEof, "truncated store"),
+                )
+            })?;
+            let record = serde_json::from_slice(line).map_err(|err| {
+                OxdError::io(&path, io::Error::new(io::ErrorKind::InvalidData, err))
+            })?;
+            records.push(record);
+        }
+        Ok(records)
+    }
+
+    fn lexical_entry(&self, record: &Record) -> LexicalEntry {
+        let mut pronunciations: Vec<Pronunciation> = vec![];
+        for sound in &record.sounds {
+            let dialects = sound
+                .tags
+                .iter()
+                .filter(|tag| REGION_TAGS.contains(&tag.as_str()))
+                .cloned()
+                .collect::<Vec<_>>();
+            let dialects = (!dialects.is_empty()).then_some(dialects);
+            if let Some(ipa) = &sound.ipa {
+                pronunciations.push(Pronunciation {
+                    phonetic_spelling: Some(ipa.trim_matches(['/', '[', ']']).to_owned()),
+                    phonetic_notation: Some("IPA".to_owned()),
+                    audio_file: None,
+                    dialects: dialects.clone(),
+                });
+            }
+            // Rodio plays both, but MP3 files are smaller.
+            if let Some(url) = sound.mp3_url.as_ref().or(sound.ogg_url.as_ref()) {
+                pronunciations.push(Pronunciation {
+                    phonetic_spelling: None,
+                    phonetic_notation: None,
+                    audio_file: Some(url.clone()),
+                    dialects,
+                });
+            }
+        }
+
+        let mut senses: Vec<Sense> = vec![];
+        for record_sense in &record.senses {
+            let Some(gloss) = record_sense.glosses.last() else {
+                continue;
+            };
+            let sense = sense(record_sense, gloss);
+            // A sense whose first gloss is the previous sense's is one of its subsenses.
+            let parent = senses.last_mut().filter(|parent| {
+                record_sense.glosses.len() > 1
+                    && parent.definitions.as_ref().and_then(|d| d.first())
+                        == record_sense.glosses.first()
+            });
+            match parent {
+                Some(parent) => parent.subsenses.get_or_insert_with(Vec::new).push(sense),
+                None => senses.push(sense),
+            }
+        }
+
+        LexicalEntry {
+            entries: vec![Entry {
+                senses: Some(senses),
+                pronunciations: (!pronunciations.is_empty()).then_some(pronunciations),
+                variant_forms: None,
+                origins: record.etymology_text.clone().map(|text| vec![text]),
+                grammatical_features: None,
+                notes: None,
+            }],
+            language: self.language.clone(),
+            lexical_category: lexical_category(&record.pos),
+            derivative_of: None,
+            grammatical_features: None,
+            text: record.word.clone(),
+        }
+    }
+}
+
+impl DictionaryBackend for Wiktionary {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        let id = word_id(word);
+        let records = self.records(&id)?;
+        if records.is_empty() {
+            return Err(OxdError::NotFound(word.to_owned()));
+        }
+        // Records of the same spelling and etymology make up one headword.
+        let mut headword_entries: Vec<(String, Option<u32>, HeadwordEntry)> = vec![];
+        for record in &records {
+            let lexical_entry = self.lexical_entry(record);
+            let key = (&record.word, record.etymology_number);
+            match headword_entries
+                .iter_mut()
+                .find(|(word, number, _)| (word, *number) == key)
+            {
+                Some((_, _, headword)) => headword.lexical_entries.push(lexical_entry),
+                None => headword_entries.push((
+                    record.word.clone(),
+                    record.etymology_number,
+                    HeadwordEntry {
+                        id: id.clone(),
+                        language: self.language.clone(),
+                        lexical_entries: vec![lexical_entry],
+                        type_: "headword".to_owned(),
+                        word: record.word.clone(),
+                    },
+                )),
+            }
+        }
+        Ok(RetrieveEntry {
+            id: Some(id),
+            metadata: metadata(),
+            headword_entries: headword_entries
+                .into_iter()
+                .map(|(_, _, headword)| headword)
+                .collect(),
+            word: Some(records[0].word.clone()),
+        })
+    }
+
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        let id = word_id(word);
+        let mut lexical_entries: Vec<LemmaLexicalEntry> = vec![];
+        // A headword that is also an inflection, e.g. "leaves", is kept as itself.
+        for record in self.records(&id)? {
+            lexical_entries.push(lemma_entry(
+                &self.language,
+                word,
+                &record.word,
+                &record.pos,
+                "",
+            ));
+        }
+        for row in self.forms.rows(&id) {
+            if let [base, pos, tags] = row[..] {
+                lexical_entries.push(lemma_entry(&self.language, word, base, pos, tags));
+            }
+        }
+        if lexical_entries.is_empty() {
+            return Err(OxdError::NotFound(word.to_owned()));
+        }
+        Ok(Lemmatron {
+            metadata: metadata(),
+            results: vec![LemmaHeadword {
+                id,
+                language: self.language.clone(),
+                lexical_entries,
+                type_: "headword".to_owned(),
+                word: word.to_owned(),
+            }],
+        })
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        let words: Vec<String> = self.index.keys().map(|id| id.replace('_', " ")).collect();
+        Ok(backend::similar_words(
+            query,
+            words.iter().map(String::as_str),
+            limit,
+        ))
+    }
+
+    /// Audio files stay on Wikimedia's servers, so they are fetched when played.
+    #[cfg(feature = "blocking")]
+    fn audio(&self, url: &str) -> Result<Vec<u8>, OxdError> {
+        let response = reqwest::blocking::get(url)?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(OxdError::NotFound(url.to_owned()));
+        }
+        Ok(response.error_for_status()?.bytes()?.to_vec())
+    }
+}
+
+fn metadata() -> serde_json::Value {
+    json!({"provider": "Wiktionary"})
+}
+
+fn lemma_entry(language: &str, word: &str, base: &str, pos: &str, tags: &str) -> LemmaLexicalEntry {
+    let features: Vec<GrammaticalFeature> = tags
+        .split_whitespace()
+        .filter(|tag| *tag != "form-of")
+        .map(|tag| GrammaticalFeature {
+            id: tag.to_owned(),
+            text: tag.replace('-', " "),
+            type_: "wiktionary".to_owned(),
+        })
+        .collect();
+    LemmaLexicalEntry {
+        grammatical_features: (!features.is_empty()).then_some(features),
+        inflection_of: vec![InflectionOf {
+            id: word_id(base),
+            text: base.to_owned(),
+        }],
+        language: language.to_owned(),
+        lexical_category: lexical_category(pos),
+        text: word.to_owned(),
+    }
+}
+
+fn sense(record_sense: &RecordSense, gloss: &str) -> Sense {
+    let labels = |tags: &[&str]| -> Vec<(String, String)> {
+        record_sense
+            .tags
+            .iter()
+            .filter(|tag| tags.contains(&tag.as_str()))
+            .map(|tag| (tag.to_lowercase(), tag.replace('-', " ")))
+            .collect()
+    };
+    let registers: Vec<Register> = labels(&REGISTER_TAGS)
+        .into_iter()
+        .map(|(id, text)| Register { id, text })
+        .collect();
+    let regions: Vec<Region> = labels(&REGION_TAGS)
+        .into_iter()
+        .map(|(id, text)| Region { id, text })
+        .collect();
+    let domains: Vec<Domain> = record_sense
+        .topics
+        .iter()
+        .map(|topic| Domain {
+            id: topic.clone(),
+            text: topic.replace('-', " "),
+        })
+        .collect();
+    let examples: Vec<Example> = record_sense
+        .examples
+        .iter()
+        .map(|example| Example {
+            text: example.text.clone(),
+            notes: None,
+            translations: example.translation.as_ref().map(|text| {
+                vec![Translation {
+                    text: text.clone(),
+                    language: "en".to_owned(),
+                    domains: None,
+                    registers: None,
+                    regions: None,
+                    notes: None,
+                    grammatical_features: None,
+                }]
+            }),
+        })
+        .collect();
+    Sense {
+        id: record_sense.id.clone(),
+        definitions: Some(vec![gloss.to_owned()]),
+        examples: (!examples.is_empty()).then_some(examples),
+        subsenses: None,
+        domains: (!domains.is_empty()).then_some(domains),
+        cross_reference_markers: None,
+        registers: (!registers.is_empty()).then_some(registers),
+        regions: (!regions.is_empty()).then_some(regions),
+        notes: None,
+        translations: None,
+        sentences: None,
+    }
+}
+
+/// The OD name of a kaikki part of speech, e.g. `adjective` for `adj`.
+fn lexical_category(pos: &str) -> LexicalCategory {
+    let id = match pos {
+        "adj" => "adjective",
+        "adv" => "adverb",
+        "name" => "proper_noun",
+        "pron" => "pronoun",
+        "prep" => "preposition",
+        "conj" => "conjunction",
+        "intj" => "interjection",
+        "num" => "numeral",
+        "det" => "determiner",
+        "abbrev" => "abbreviation",
+        "" => "other",
+        pos => pos,
+    };
+    let mut text = id.replace('_', " ");
+    if let Some(first) = text.get_mut(..1) {
+        first.make_ascii_uppercase();
+    }
+    LexicalCategory {
+        id: id.to_owned(),
+        text,
+    }
+}
+
+#[test]
+fn test_form_line() {
+    let tags = ["past".to_owned(), "odd\ttag".to_owned()];
+    assert_eq!(
+        form_line("Rusted", "rust", "verb\tnoun", &tags),
+        "rusted\trust\t\tpast\n"
+    );
+}
+
+#[test]
+fn test_lexical_category() {
+    assert_eq!(lexical_category("adj").id, "adjective");
+    assert_eq!(lexical_category("name").text, "Proper noun");
+    assert_eq!(lexical_category("suffix").text, "Suffix");
+}
@@ -0,0 +1,465 @@
+/*! A [DictionaryBackend] reading a local Princeton WordNet database
+ *
+ * Reads the `index.*`, `data.*` and `*.exc` files of a WordNet 3 `dict/` directory,
+ * as installed by most package managers, so lookups need neither network nor credentials.
+ *
+ * Each synset of a word becomes a [Sense], with its gloss split into
+ * definitions and examples and the other words of the synset as a note.
+ * Senses are grouped into one [LexicalEntry] per part of speech.
+ * Inflected forms are resolved to headwords by the exception lists
+ * and the suffix rules WordNet's own morphy uses.
+ */
+
+use crate::backend::{self, DictionaryBackend};
+use crate::error::OxdError;
+use crate::lookup::word_id;
+use crate::models::lemmas::{InflectionOf, LemmaHeadword, LemmaLexicalEntry, Lemmatron};
+use crate::models::search::SearchResults;
+use crate::models::{
+    Entry, Example, HeadwordEntry, LexicalCategory, LexicalEntry, Note, RetrieveEntry, Sense,
+};
+use serde_json::json;
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, File};
+use std::io::{self, BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// The parts of speech WordNet distinguishes, each with its own files.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum PartOfSpeech {
+    Noun,
+    Verb,
+    Adjective,
+    Adverb,
+}
+
+impl PartOfSpeech {
+    const ALL: [PartOfSpeech; 4] = [
+        PartOfSpeech::Noun,
+        PartOfSpeech::Verb,
+        PartOfSpeech::Adjective,
+        PartOfSpeech::Adverb,
+    ];
+
+    /// The suffix of the database files, e.g. `adj` in `index.adj`.
+    fn file_suffix(self) -> &'static str {
+        match self {
+            PartOfSpeech::Noun => "noun",
+            PartOfSpeech::Verb => "verb",
+            PartOfSpeech::Adjective => "adj",
+            PartOfSpeech::Adverb => "adv",
+        }
+    }
+
+    fn lexical_category(self) -> LexicalCategory {
+        let (id, text) = match self {
+            PartOfSpeech::Noun => ("noun", "Noun"),
+            PartOfSpeech::Verb => ("verb", "Verb"),
+            PartOfSpeech::Adjective => ("adjective", "Adjective"),
+            PartOfSpeech::Adverb => ("adverb", "Adverb"),
+        };
+        LexicalCategory {
+            id: id.to_owned(),
+            text: text.to_owned(),
+        }
+    }
+
+    /// Suffixes of inflected forms and what replaces them in the base form.
+    fn detachments(self) -> &'static [(&'static str, &'static str)] {
+        match self {
+            PartOfSpeech::Noun => &[
+                ("s", ""),
+                ("ses", "s"),
+                ("xes", "x"),
+                ("zes", "z"),
+                ("ches", "ch"),
+                ("shes", "sh"),
+                ("men", "man"),
+                ("ies", "y"),
+            ],
+            PartOfSpeech::Verb => &[
+                ("s", ""),
+                ("ies", "y"),
+                ("es", "e"),
+                ("es", ""),
+                ("ed", "e"),
+                ("ed", ""),
+                ("ing", "e"),
+                ("ing", ""),
+            ],
+            PartOfSpeech::Adjective => &[("er", ""), ("est", ""), ("er", "e"), ("est", "e")],
+            PartOfSpeech::Adverb => &[],
+        }
+    }
+}
+
+/// The words of one part of speech, loaded when the database is opened.
+#[derive(Debug, Default)]
+struct Index {
+    /// Synset offsets in `data.*` for each lemma, e.g. `ice_cream`.
+    synsets: HashMap<String, Vec<u64>>,
+    /// Base forms of irregular inflections, e.g. `mouse` for `mice`.
+    exceptions: HashMap<String, Vec<String>>,
+}
+
+/// A local WordNet database.
+///
+/// ```no_run
+/// use oxd::wordnet::WordNet;
+/// use oxd::DictionaryBackend;
+///
+/// let wordnet = WordNet::open("/usr/share/wordnet").unwrap();
+/// let lookup = wordnet.lookup("rusted").unwrap();
+/// ```
+#[derive(Debug)]
+pub struct WordNet {
+    dir: PathBuf,
+    indexes: Vec<(PartOfSpeech, Index)>,
+}
+
+impl WordNet {
+    /// Opens the `dict/` directory of a WordNet installation.
+    /// Parts of speech whose files are missing are left out.
+    pub fn open(dir: impl Into<PathBuf>) -> Result<Self, OxdError> {
+        let dir = dir.into();
+        let mut indexes = vec![];
+        for pos in PartOfSpeech::ALL {
+            let index_file = dir.join(format!("index.{}", pos.file_suffix()));
+            let Some(synsets) = read_lines(&index_file, parse_index_line)? else {
+                continue;
+            };
+            let exception_file = dir.join(format!("{}.exc", pos.file_suffix()));
+            let exceptions = read_lines(&exception_file, parse_exception_line)?;
+            let index = Index {
+                synsets: synsets.into_iter().collect(),
+                exceptions: exceptions.unwrap_or_default().into_iter().collect(),
+            };
+            indexes.push((pos, index));
+        }
+        if indexes.is_empty() {
+            return Err(OxdError::InvalidConfig(format!(
+                "no WordNet database in {}",
+                dir.display()
+            )));
+        }
+        Ok(WordNet { dir, indexes })
+    }
+
+    /// Opens the database in `$WNSEARCHDIR`, `$WNHOME/dict` or `/usr/share/wordnet`,
+    /// the places WordNet's own tools look.
+    pub fn open_default() -> Result<Self, OxdError> {
+        WordNet::open(WordNet::default_dir())
+    }
+
+    pub fn default_dir() -> PathBuf {
+        if let Some(dir) = env::var_os("WNSEARCHDIR") {
+            return PathBuf::from(dir);
+        }
+        match env::var_os("WNHOME") {
+            Some(home) => PathBuf::from(home).join("dict"),
+            None => PathBuf::from("/usr/share/wordnet"),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// The base forms of `word` known as `pos`, the word itself included if it is one.
+    fn base_forms(&self, word: &str, pos: PartOfSpeech, index: &Index) -> Vec<String> {
+        let mut forms: Vec<String> = vec![];
+        let mut add = |form: String| {
+            if index.synsets.contains_key(&form) && !forms.contains(&form) {
+                forms.push(form);
+            }
+        };
+        add(word.to_owned());
+        for base in index.exceptions.get(word).into_iter().flatten() {
+            add(base.clone());
+        }
+        for (suffix, ending) in pos.detachments() {
+            if let Some(stem) = word.strip_suffix(suffix) {
+                add(format!("{stem}{ending}"));
+            }
+        }
+        forms
+    }
+
+    /// Reads the synset at `offset` in the data file of `pos`.
+    fn synset(&self, pos: PartOfSpeech, offset: u64) -> Result<Synset, OxdError> {
+        let path = self.dir.join(format!("data.{}", pos.file_suffix()));
+        let mut line = String::new();
+        let mut file = File::open(&path).map_err(|source| OxdError::io(&path, source))?;
+        file.seek(SeekFrom::Start(offset))
+            .and_then(|_| BufReader::new(file).read_line(&mut line))
+            .map_err(|source| OxdError::io(&path, source))?;
+        parse_synset(&line).ok_or_else(|| {
+            OxdError::InvalidConfig(format!(
+                "no synset at offset {offset} in {}",
+                path.display()
+            ))
+        })
+    }
+}
+
+impl DictionaryBackend for WordNet {
+    fn entry(&self, word: &str) -> Result<RetrieveEntry, OxdError> {
+        let id = word_id(word);
+        let mut lexical_entries = vec![];
+        for (pos, index) in &self.indexes {
+            let Some(offsets) = index.synsets.get(&id) else {
+                continue;
+            };
+            let mut senses = vec![];
+            for &offset in offsets {
+                let synset = self.synset(*pos, offset)?;
+                senses.push(synset.sense(&id, *pos, offset));
+            }
+            lexical_entries.push(LexicalEntry {
+                entries: vec![Entry {
+                    senses: Some(senses),
+                    pronunciations: None,
+                    variant_forms: None,
+                    origins: None,
+                    grammatical_features: None,
+                    notes: None,
+                }],
+                language: LANGUAGE.to_owned(),
+                lexical_category: pos.lexical_category(),
+                derivative_of: None,
+                grammatical_features: None,
+                text: display_form(&id),
+            });
+        }
+        if lexical_entries.is_empty() {
+            return Err(OxdError::NotFound(word.to_owned()));
+        }
+        Ok(RetrieveEntry {
+            id: Some(id.clone()),
+            metadata: metadata(),
+            headword_entries: vec![HeadwordEntry {
+                id: id.clone(),
+                language: LANGUAGE.to_owned(),
+                lexical_entries,
+                type_: "headword".to_owned(),
+                word: display_form(&id),
+            }],
+            word: Some(display_form(&id)),
+        })
+    }
+
+    fn lemmas(&self, word: &str) -> Result<Lemmatron, OxdError> {
+        let id = word_id(word);
+        let lexical_entries: Vec<LemmaLexicalEntry> = self
+            .indexes
+            .iter()
+            .filter_map(|(pos, index)| {
+                let forms = self.base_forms(&id, *pos, index);
+                (!forms.is_empty()).then(|| LemmaLexicalEntry {
+                    grammatical_features: None,
+                    inflection_of: forms
+                        .iter()
+                        .map(|form| InflectionOf {
+                            id: form.clone(),
+                            text: display_form(form),
+                        })
+                        .collect(),
+                    language: LANGUAGE.to_owned(),
+                    lexical_category: pos.lexical_category(),
+                    text: display_form(&id),
+                })
+            })
+            .collect();
+        if lexical_entries.is_empty() {
+            return Err(OxdError::NotFound(word.to_owned()));
+        }
+        Ok(Lemmatron {
+            metadata: metadata(),
+            results: vec![LemmaHeadword {
+                id: id.clone(),
+                language: LANGUAGE.to_owned(),
+                lexical_entries,
+                type_: "headword".to_owned(),
+                word: display_form(&id),
+            }],
+        })
+    }
+
+    fn search(&self, query: &str, limit: usize) -> Result<SearchResults, OxdError> {
+        let words: Vec<String> = self
+            .indexes
+            .iter()
+            .flat_map(|(_, index)| index.synsets.keys())
+            .map(|id| display_form(id))
+            .collect();
+        Ok(backend::similar_words(
+            query,
+            words.iter().map(String::as_str),
+            limit,
+        ))
+    }
+}
+
+const LANGUAGE: &str = "en";
+
+fn metadata() -> serde_json::Value {
+    json!({"provider": "Princeton WordNet"})
+}
+
+/// Turns a WordNet lemma such as `ice_cream` back into "ice cream".
+fn display_form(lemma: &str) -> String {
+    lemma.replace('_', " ")
+}
+
+/// One line of a `data.*` file: a set of synonyms and their gloss.
+#[derive(Debug, PartialEq)]
+struct Synset {
+    words: Vec<String>,
+    gloss: String,
+}
+
+impl Synset {
+    /// The sense of `id` this synset stands for.
+    fn sense(&self, id: &str, pos: PartOfSpeech, offset: u64) -> Sense {
+        let (definitions, examples) = split_gloss(&self.gloss);
+        let synonyms: Vec<String> = self
+            .words
+            .iter()
+            .filter(|word| word_id(word) != id)
+            .map(|word| display_form(word))
+            .collect();
+        let note = Note {
+            id: None,
+            text: format!("also {}", synonyms.join(", ")),
+            type_: "synonyms".to_owned(),
+        };
+        Sense {
+            id: Some(format!("wn-{}-{offset:08}", pos.file_suffix())),
+            definitions: (!definitions.is_empty()).then_some(definitions),
+            examples: (!examples.is_empty()).then(|| {
+                examples
+                    .into_iter()
+                    .map(|text| Example {
+                        text,
+                        notes: None,
+                        translations: None,
+                    })
+                    .collect()
+            }),
+            subsenses: None,
+            domains: None,
+            cross_reference_markers: None,
+            registers: None,
+            regions: None,
+            notes: (!synonyms.is_empty()).then(|| vec![note]),
+            translations: None,
+            sentences: None,
+        }
+    }
+}
+
+/// Parses `offset lex_filenum ss_type w_cnt word lex_id ... | gloss`,
+/// where `w_cnt` is hexadecimal.
+fn parse_synset(line: &str) -> Option<Synset> {
+    let (fields, gloss) = line.split_once(" | ").unwrap_or((line, ""));
+    let mut fields = fields.split_whitespace().skip(3);
+    let count = usize::from_str_radix(fields.next()?, 16).ok()?;
+    let mut words = vec![];
+    for _ in 0..count {
+        let word = fields.next()?;
+        fields.next()?;
+        // Adjectives may carry a syntactic marker such as `(a)` or `(ip)`.
+        let word = word.split_once('(').map_or(word, |(word, _)| word);
+        words.push(word.to_lowercase());
+    }
+    Some(Synset {
+        words,
+        gloss: gloss.trim().to_owned(),
+    })
+}
+
+/// Splits a gloss such as `become coated with oxide; "the pipes rusted"`
+/// into definitions and quoted examples.
+fn split_gloss(gloss: &str) -> (Vec<String>, Vec<String>) {
+    let mut definitions = vec![];
+    let mut examples = vec![];
+    for part in gloss
+        .split(';')
+        .map(str::trim)
+        .filter(|part| !part.is_empty())
+    {
+        match part.strip_prefix('"') {
+            Some(example) => examples.push(example.trim_end_matches('"').to_owned()),
+            None => definitions.push(part.to_owned()),
+        }
+    }
+    (definitions, examples)
+}
+
+/// Parses `lemma pos synset_cnt p_cnt [ptr_symbol...] sense_cnt tagsense_cnt synset_offset...`.
+fn parse_index_line(line: &str) -> Option<(String, Vec<u64>)> {
+    let mut fields = line.split_whitespace();
+    let lemma = fields.next()?.to_owned();
+    let synset_count: usize = fields.nth(1)?.parse().ok()?;
+    let pointer_count: usize = fields.next()?.parse().ok()?;
+    let offsets = fields
+        .skip(pointer_count + 2)
+        .take(synset_count)
+        .map(str::parse)
+        .collect::<Result<_, _>>()
+        .ok()?;
+    Some((lemma, offsets))
+}
+
+/// Parses `inflected base...`.
+fn parse_exception_line(line: &str) -> Option<(String, Vec<String>)> {
+    let mut fields = line.split_whitespace().map(str::to_owned);
+    Some((fields.next()?, fields.collect()))
+}
+
+/// Parses the lines of a database file, skipping the license lines at its top,
+/// or returns `None` if the file does not exist.
+fn read_lines<T>(
+    path: &Path,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Option<Vec<T>>, OxdError> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(source) => return Err(OxdError::io(path, source)),
+    };
+    let parsed = text
+        .lines()
+        .filter(|line| !line.starts_with("  "))
+        .filter_map(parse)
+        .collect();
+    Ok(Some(parsed))
+}
+
+#[test]
+fn test_parse_synset() {
+    let line = "00001740 03 n 02 rust 0 rust_fungus 0 001 @ 00002000 n 0000 \
+        | any of various fungi causing rust disease in plants; \"the wheat had rust\"  \n";
+    let synset = parse_synset(line).unwrap();
+    assert_eq!(synset.words, ["rust", "rust_fungus"]);
+    let sense = synset.sense("rust", PartOfSpeech::Noun, 1740);
+    assert_eq!(sense.id.as_deref(), Some("wn-noun-00001740"));
+    assert_eq!(
+        sense.definitions.unwrap(),
+        ["any of various fungi causing rust disease in plants"]
+    );
+    assert_eq!(sense.examples.unwrap()[0].text, "the wheat had rust");
+    assert_eq!(sense.notes.unwrap()[0].text, "also rust fungus");
+
+    let adjective = parse_synset("00002000 00 s 01 rusty(p) 0 000 | covered with rust").unwrap();
+    assert_eq!(adjective.words, ["rusty"]);
+}
+
+#[test]
+fn test_parse_index_line() {
+    let line = "rust n 2 3 @ ~ + 2 0 00001740 00002000  ";
+    let (lemma, offsets) = parse_index_line(line).unwrap();
+    assert_eq!(lemma, "rust");
+    assert_eq!(offsets, [1740, 2000]);
+}
@@ -0,0 +1,89 @@
+#![cfg(feature = "async")]
+
+mod common;
+
+use common::{temp_dir, MockServer, Response};
+use oxd::cache::ResponseCache;
+use oxd::models::catalog::LabelKind;
+use oxd::OxdError;
+
+#[tokio::test]
+async fn entries_include_roots_before_the_word() {
+    let server = MockServer::fixtures();
+    let client = server.builder().build_async().unwrap();
+    let lookup = client.entries("rusty").await.unwrap();
+    let words: Vec<_> = lookup
+        .in_display_order()
+        .iter()
+        .map(|found| found.entry.headword_entries[0].word.as_str())
+        .collect();
+    assert_eq!(words, ["rust", "rusty"]);
+    assert_eq!(server.requests()[0].header("app_key"), Some("test_key"));
+}
+
+#[tokio::test]
+async fn audio_is_fetched_without_credentials() {
+    let server = MockServer::start(|req| match req.path() {
+        "/audio/rust.mp3" => Response::json("ID3"),
+        _ => Response::status(404),
+    });
+    let client = server.builder().build_async().unwrap();
+    let url = server.url.replace("/api/v2/", "/audio/rust.mp3");
+    assert_eq!(client.audio(&url).await.unwrap(), b"ID3");
+    assert_eq!(server.requests()[0].header("app_id"), None);
+
+    let missing = server.url.replace("/api/v2/", "/audio/none.mp3");
+    assert!(matches!(
+        client.audio(&missing).await,
+        Err(OxdError::NotFound(_))
+    ));
+}
+
+#[tokio::test]
+async fn many_entries_share_roots_and_keep_their_order() {
+    let server = MockServer::fixtures();
+    let client = server.builder().build_async().unwrap();
+    let lookups = client.get_many(&["rusted", "qwxz", "rusty"], 2).await;
+
+    assert_eq!(lookups[0].as_ref().unwrap().word, "rusted");
+    assert!(matches!(lookups[1], Err(OxdError::NotFound(_))));
+    assert_eq!(lookups[2].as_ref().unwrap().entries.len(), 2);
+    let rust_fetches = server
+        .requests()
+        .iter()
+        .filter(|req| req.target == "/api/v2/words/en-us?q=rust")
+        .count();
+    assert_eq!(rust_fetches, 1);
+}
+
+#[tokio::test]
+async fn cached_responses_are_reused_offline() {
+    let dir = temp_dir("async-cache");
+    let server = MockServer::fixtures();
+    let online = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .build_async()
+        .unwrap();
+    online.entry("rust").await.unwrap();
+    online.labels(LabelKind::Domains).await.unwrap();
+    let sent = server.requests().len();
+
+    let offline = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .offline(true)
+        .build_async()
+        .unwrap();
+    offline.entry("rust").await.unwrap();
+    assert!(offline
+        .labels(LabelKind::Domains)
+        .await
+        .unwrap()
+        .contains("computing"));
+    assert!(matches!(
+        offline.entry("rusty").await,
+        Err(OxdError::NotCached(_))
+    ));
+    assert_eq!(server.requests().len(), sent);
+}
@@ -0,0 +1,67 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{fixture_path, temp_dir, MockServer};
+use oxd::backend::{DictionaryBackend, MemoryBackend};
+use oxd::cache::ResponseCache;
+use oxd::display::Display;
+use oxd::{Language, OxdError};
+
+/// Headwords of a lookup in the order oxd shows them.
+fn shown(lookup: &oxd::Lookup) -> Vec<&str> {
+    lookup
+        .in_display_order()
+        .iter()
+        .map(|found| found.entry.headword_entries[0].word.as_str())
+        .collect()
+}
+
+#[test]
+fn memory_backend_looks_up_like_the_client() {
+    let backend = MemoryBackend::load_dir(fixture_path("")).unwrap();
+    let client = MockServer::fixtures().client();
+    let backends: [&dyn DictionaryBackend; 2] = [&backend, &client];
+    for backend in backends {
+        assert_eq!(shown(&backend.lookup("rusty").unwrap()), ["rust", "rusty"]);
+        let lookup = backend.lookup("rusted").unwrap();
+        assert_eq!(shown(&lookup), ["rust"]);
+        let mut canvas = String::new();
+        lookup.lemmas.display(&mut canvas);
+        assert!(canvas.contains("rusted → rust (past)"));
+        assert!(matches!(backend.lookup("qwxz"), Err(OxdError::NotFound(_))));
+    }
+
+    let suggestions = backend.search("rsut", 3).unwrap();
+    assert_eq!(suggestions.ranked()[0].word, "rust");
+    let backend = backend.with_audio("rust.mp3", b"ID3".to_vec());
+    assert_eq!(backend.audio("rust.mp3").unwrap(), b"ID3");
+    assert!(backend.audio("iron.mp3").is_err());
+}
+
+#[test]
+fn cache_backend_answers_from_cached_responses() {
+    let dir = temp_dir("backend");
+    let server = MockServer::fixtures();
+    let client = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .build()
+        .unwrap();
+    client.entries("rusty").unwrap();
+    let requests = server.requests().len();
+
+    let backend = ResponseCache::new(dir.path()).backend(Language::EnUs);
+    assert_eq!(shown(&backend.lookup("rusty").unwrap()), ["rust", "rusty"]);
+    assert_eq!(backend.search("rust", 5).unwrap().results.len(), 2);
+    assert!(matches!(
+        backend.lookup("iron"),
+        Err(OxdError::NotCached(word)) if word == "iron"
+    ));
+    assert!(matches!(backend.entry("iron"), Err(OxdError::NotCached(_))));
+    assert!(ResponseCache::new(dir.path())
+        .backend(Language::EnGb)
+        .lookup("rusty")
+        .is_err());
+    assert_eq!(server.requests().len(), requests);
+}
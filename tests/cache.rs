@@ -0,0 +1,86 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{temp_dir, MockServer};
+use oxd::cache::ResponseCache;
+use oxd::OxdError;
+use std::time::Duration;
+
+#[test]
+fn cache_reports_exports_and_purges_responses() {
+    let dir = temp_dir("manage");
+    let server = MockServer::fixtures();
+    let client = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .build()
+        .unwrap();
+    client.entries("rusty").unwrap();
+    client.lemmas("missing").unwrap_err();
+
+    let cache = ResponseCache::new(dir.path());
+    let stats = cache.stats().unwrap();
+    assert_eq!(stats.responses, 3);
+    assert_eq!(stats.per_language["en-us"], 2);
+    assert_eq!(stats.per_language["en"], 1);
+    assert!(stats.size > 0);
+
+    let found = cache.find("Rusty").unwrap();
+    let endpoints: Vec<_> = found.iter().map(|c| c.key.endpoint.as_str()).collect();
+    assert_eq!(endpoints, ["lemmas", "words"]);
+
+    let archive = temp_dir("manage.jsonl");
+    assert_eq!(cache.export(archive.path()).unwrap(), 3);
+    let copy_dir = temp_dir("import");
+    let copy = ResponseCache::new(copy_dir.path());
+    assert_eq!(copy.import(archive.path()).unwrap(), 3);
+    assert_eq!(copy.import(archive.path()).unwrap(), 0);
+    assert!(matches!(
+        copy.import(dir.with_extension("missing")),
+        Err(OxdError::Io { path, .. }) if path == dir.with_extension("missing")
+    ));
+    assert_eq!(copy.stats().unwrap(), stats);
+
+    assert_eq!(cache.purge(Some(Duration::from_secs(3600))).unwrap(), 0);
+    assert_eq!(cache.purge(None).unwrap(), 3);
+    assert_eq!(cache.stats().unwrap().responses, 0);
+}
+
+#[test]
+fn import_rejects_keys_outside_the_cache() {
+    let dir = temp_dir("hostile");
+    let archive = temp_dir("hostile.jsonl");
+    for (endpoint, language) in [("../../x", "en-us"), ("/tmp", "en-us"), ("words", "..\\x")] {
+        let line = serde_json::json!({
+            "endpoint": endpoint,
+            "language": language,
+            "word": "rust",
+            "filters": "",
+            "fetched_at": 0,
+            "body": "{}",
+        });
+        std::fs::write(archive.path(), format!("{line}\n")).unwrap();
+        let cache = ResponseCache::new(dir.path());
+        assert!(matches!(
+            cache.import(archive.path()),
+            Err(OxdError::Io { source, .. }) if source.kind() == std::io::ErrorKind::InvalidData
+        ));
+        assert!(!dir.exists());
+    }
+}
+
+#[test]
+fn purge_removes_the_files_it_finds() {
+    let dir = temp_dir("renamed");
+    let cache = ResponseCache::new(dir.path());
+    let archive = temp_dir("renamed.jsonl");
+    let line = r#"{"endpoint":"words","language":"en-us","word":"rust","filters":"","fetched_at":0,"body":"{}"}"#;
+    std::fs::write(archive.path(), format!("{line}\n")).unwrap();
+    cache.import(archive.path()).unwrap();
+    let file = &cache.list().unwrap()[0];
+    std::fs::rename(&file.path, file.path.with_file_name("rust (copy).json")).unwrap();
+
+    assert_eq!(cache.purge(None).unwrap(), 1);
+    assert!(cache.list().unwrap().is_empty());
+}
@@ -0,0 +1,483 @@
+#![cfg(feature = "blocking")]
+
+mod common;
+
+use common::{fixture_response, temp_dir, MockServer, Response};
+use oxd::cache::ResponseCache;
+use oxd::display::Display;
+use oxd::models::catalog::LabelKind;
+use oxd::models::sentences::attach_sentences;
+use oxd::rate_limit::{Plan, RateLimiter};
+use oxd::retry::RetryPolicy;
+use oxd::{Language, LookupFilters, OxdClient, OxdError, TranslationLanguage};
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+#[test]
+fn entry_sends_credentials_and_decodes_response() {
+    let server = MockServer::fixtures();
+    let entry = server.client().entry("rust").unwrap();
+    assert_eq!(entry.headword_entries[0].word, "rust");
+
+    let requests = server.requests();
+    assert_eq!(requests[0].target, "/api/v2/words/en-us?q=rust");
+    assert_eq!(requests[0].header("app_id"), Some("test_id"));
+    assert_eq!(requests[0].header("app_key"), Some("test_key"));
+}
+
+#[test]
+fn entries_include_roots_before_the_word() {
+    let server = MockServer::fixtures();
+    let lookup = server.client().entries("rusty").unwrap();
+    let words: Vec<_> = lookup
+        .in_display_order()
+        .iter()
+        .map(|found| found.entry.headword_entries[0].word.as_str())
+        .collect();
+    assert_eq!(words, ["rust", "rusty"]);
+    assert_eq!(lookup.headwords, ["rusty"]);
+    assert_eq!(lookup.get("rust").unwrap().depth, 1);
+    assert_eq!(lookup.roots_of("rusty")[0].id, "rust");
+    assert_eq!(lookup.derivatives_of("rust")[0].id, "rusty");
+}
+
+/// An entry with one lexical entry per root, each a derivative of it.
+fn derived_entry(word: &str, roots: &[&str]) -> String {
+    let lexical_entries: Vec<String> = roots
+        .iter()
+        .map(|root| {
+            format!(
+                r#"{{"derivativeOf": [{{"id": "{root}", "text": "{root}"}}],
+                    "entries": [], "language": "en-us",
+                    "lexicalCategory": {{"id": "noun", "text": "Noun"}}, "text": "{word}"}}"#
+            )
+        })
+        .collect();
+    format!(
+        r#"{{"id": "{word}", "metadata": {{}}, "word": "{word}",
+            "results": [{{"id": "{word}", "language": "en-us", "lexicalEntries": [{}],
+                "type": "headword", "word": "{word}"}}]}}"#,
+        lexical_entries.join(",")
+    )
+}
+
+#[test]
+fn roots_are_followed_to_a_depth_and_cycles_end() {
+    let server = MockServer::start(
+        |req| match req.target.split_once("q=").map(|(_, word)| word) {
+            Some("ferrous") => Response::json(derived_entry("ferrous", &["iron", "iron"])),
+            Some("iron") => Response::json(derived_entry("iron", &["metal"])),
+            Some("metal") => Response::json(derived_entry("metal", &["ferrous"])),
+            _ => Response::status(404),
+        },
+    );
+    let lookup = server.client().entries("ferrous").unwrap();
+    let found: Vec<_> = lookup
+        .entries
+        .iter()
+        .map(|found| (found.id.as_str(), found.depth))
+        .collect();
+    assert_eq!(found, [("ferrous", 0), ("iron", 1), ("metal", 2)]);
+    assert_eq!(lookup.get("ferrous").unwrap().derivative_of, ["iron"]);
+    assert_eq!(lookup.get("metal").unwrap().derivative_of, ["ferrous"]);
+    let order: Vec<_> = lookup
+        .in_display_order()
+        .iter()
+        .map(|found| found.id.as_str())
+        .collect();
+    assert_eq!(order, ["metal", "iron", "ferrous"]);
+    let word_requests = server
+        .requests()
+        .iter()
+        .filter(|req| req.path() == "/api/v2/words/en-us")
+        .count();
+    assert_eq!(word_requests, 3);
+
+    let shallow = server.builder().root_depth(1).build().unwrap();
+    let lookup = shallow.entries("ferrous").unwrap();
+    assert_eq!(lookup.entries.len(), 2);
+    assert_eq!(lookup.get("iron").unwrap().derivative_of, ["metal"]);
+    assert!(lookup.roots_of("iron").is_empty());
+}
+
+#[test]
+fn entries_resolve_inflected_forms_through_lemmas() {
+    let server = MockServer::fixtures();
+    let lookup = server.client().entries("rusted").unwrap();
+    assert_eq!(lookup.entries.len(), 1);
+    assert_eq!(lookup.entries[0].entry.headword_entries[0].word, "rust");
+
+    let mut canvas = String::new();
+    lookup.lemmas.display(&mut canvas);
+    assert!(canvas.contains("rusted → rust (past)"));
+    assert!(!canvas.contains("rusted → rusted"));
+}
+
+#[test]
+fn statuses_map_to_errors() {
+    let server = MockServer::start(|req| match req.path() {
+        "/api/v2/words/en-us" if req.target.ends_with("forbidden") => Response::status(403),
+        "/api/v2/words/en-us" if req.target.ends_with("busy") => {
+            Response::status(429).header("Retry-After", "7")
+        }
+        "/api/v2/words/en-us" if req.target.ends_with("broken") => Response::status(502),
+        "/api/v2/words/en-us" if req.target.ends_with("garbled") => Response::json("{}"),
+        _ => Response::status(404),
+    });
+    let client = server.client();
+
+    assert!(matches!(
+        client.entry("forbidden"),
+        Err(OxdError::Unauthorized(_))
+    ));
+    assert!(matches!(
+        client.entry("busy"),
+        Err(OxdError::RateLimited { retry_after: Some(delay) }) if delay.as_secs() == 7
+    ));
+    assert!(matches!(client.entry("broken"), Err(OxdError::Server(_))));
+    assert!(matches!(
+        client.entry("garbled"),
+        Err(OxdError::Decode { .. })
+    ));
+    assert!(matches!(client.entry("missing"), Err(OxdError::NotFound(word)) if word == "missing"));
+}
+
+#[test]
+fn builder_requires_credentials() {
+    let result = OxdClient::builder().app_id("test_id").build();
+    assert!(matches!(result, Err(OxdError::InvalidConfig(_))));
+}
+
+#[test]
+fn source_lang_selects_dictionary() {
+    let server = MockServer::fixtures();
+    let client = server.builder().source_lang(Language::Es).build().unwrap();
+    let entry = client.entry("casa").unwrap();
+
+    assert_eq!(server.requests()[0].path(), "/api/v2/words/es");
+    let lexical_entry = &entry.headword_entries[0].lexical_entries[0];
+    let features = lexical_entry.entries[0]
+        .grammatical_features
+        .as_ref()
+        .unwrap();
+    assert_eq!(features[0].id, "feminine");
+}
+
+#[test]
+fn thesaurus_groups_synonyms_by_lexical_category() {
+    let server = MockServer::start(|req| match req.path() {
+        "/api/v2/thesaurus/en/rust" => fixture_response("thesaurus/rust.json"),
+        _ => Response::status(404),
+    });
+    let client = server
+        .builder()
+        .source_lang(Language::EnGb)
+        .build()
+        .unwrap();
+    let thesaurus = client.thesaurus("Rust").unwrap();
+
+    let mut canvas = String::new();
+    thesaurus.display(&mut canvas);
+    let noun = canvas.find("noun").unwrap();
+    let verb = canvas.find("verb").unwrap();
+    let corrosion = canvas.find("corrosion, oxidation").unwrap();
+    let corrode = canvas.find("corrode, oxidize").unwrap();
+    assert!(noun < corrosion && corrosion < verb && verb < corrode);
+    assert!(canvas.contains("shine"));
+}
+
+#[test]
+fn translations_render_through_display() {
+    let server = MockServer::start(|req| match req.path() {
+        "/api/v2/translations/en/es/house" => fixture_response("translations/house.json"),
+        _ => Response::status(404),
+    });
+    let spanish = "es".parse::<TranslationLanguage>().unwrap();
+    let entry = server
+        .client()
+        .translations(TranslationLanguage::ENGLISH, spanish, "house")
+        .unwrap();
+
+    let mut canvas = String::new();
+    entry.display(&mut canvas);
+    assert!(canvas.contains("casa"));
+    assert!(canvas.contains("[Latin America]"));
+    assert!(canvas.contains("una casa de tres dormitorios"));
+    assert!(canvas.contains("(audience)"));
+}
+
+#[test]
+fn sentences_attach_to_matching_senses() {
+    let server = MockServer::fixtures();
+    let client = server.client();
+    let mut entry = client.entry("rust").unwrap();
+    let sentences = client.sentences("rust").unwrap();
+    attach_sentences(&mut entry, &sentences, 2);
+
+    let lexical_entry = &entry.headword_entries[0].lexical_entries[0];
+    let sense = &lexical_entry.entries[0].senses.as_ref().unwrap()[0];
+    let attached = sense.sentences.as_ref().unwrap();
+    assert_eq!(attached.len(), 2);
+    assert_eq!(attached[0].text, "The rust had eaten through the bumper.");
+    let subsense = &sense.subsenses.as_ref().unwrap()[0];
+    assert_eq!(subsense.sentences.as_ref().unwrap().len(), 1);
+
+    let mut canvas = String::new();
+    entry.display(&mut canvas);
+    assert!(canvas.contains("Wheat rust spread across the fields."));
+    assert!(!canvas.contains("cut off"));
+}
+
+#[test]
+fn search_ranks_suggestions_by_score() {
+    let server = MockServer::fixtures();
+    let results = server.client().search("rsut", 5).unwrap();
+    assert_eq!(
+        server.requests()[0].target,
+        "/api/v2/search/en-us?q=rsut&limit=5"
+    );
+
+    let ranked: Vec<_> = results.ranked().iter().map(|r| r.word.as_str()).collect();
+    assert_eq!(ranked, ["rust", "rusty", "rut"]);
+}
+
+#[test]
+fn catalog_endpoints_are_fetched_once() {
+    let server = MockServer::start(|req| match req.path() {
+        "/api/v2/filters" => fixture_response("filters/filters.json"),
+        path => fixture_response(&format!("{}.json", path.trim_start_matches("/api/v2/"))),
+    });
+    let client = server.client();
+    let domains = client.labels(LabelKind::Domains).unwrap();
+    assert!(domains.contains("computing"));
+    assert_eq!(domains.text("computing"), "Computing");
+    client.clone().labels(LabelKind::Domains).unwrap();
+
+    let features = client.labels(LabelKind::GrammaticalFeatures).unwrap();
+    assert_eq!(
+        features.get("past").unwrap().type_.as_deref(),
+        Some("Tense")
+    );
+
+    let filters = client.filters().unwrap();
+    assert!(filters
+        .for_endpoint("entries")
+        .contains(&"lexicalCategory".to_owned()));
+    assert!(filters.for_endpoint("lemmas").is_empty());
+
+    let paths: Vec<_> = server.requests().iter().map(|r| r.target.clone()).collect();
+    assert_eq!(
+        paths,
+        [
+            "/api/v2/domains/en-us",
+            "/api/v2/grammaticalFeatures/en-us",
+            "/api/v2/filters"
+        ]
+    );
+}
+
+#[test]
+fn filters_are_sent_and_applied_to_entries() {
+    let server = MockServer::fixtures();
+    let client = server.client();
+
+    let verbs = LookupFilters::default().lexical_category("verb");
+    let entry = client.entry_with("rust", &verbs).unwrap();
+    assert_eq!(
+        server.requests()[0].target,
+        "/api/v2/words/en-us?q=rust&lexicalCategory=verb"
+    );
+    let lexical_entries = &entry.headword_entries[0].lexical_entries;
+    assert_eq!(lexical_entries.len(), 1);
+    assert_eq!(lexical_entries[0].lexical_category.id, "verb");
+
+    let botany = LookupFilters::default().domain("Botany");
+    let entry = client.entry_with("rust", &botany).unwrap();
+    let lexical_entry = &entry.headword_entries[0].lexical_entries[0];
+    let sense = &lexical_entry.entries[0].senses.as_ref().unwrap()[0];
+    let subsenses = sense.subsenses.as_ref().unwrap();
+    assert_eq!(subsenses.len(), 1);
+    assert_eq!(subsenses[0].domains.as_ref().unwrap()[0].id, "botany");
+
+    let computing = LookupFilters::default().domain("computing");
+    assert!(matches!(
+        client.entry_with("rust", &computing),
+        Err(OxdError::NotFound(_))
+    ));
+}
+
+#[test]
+fn transient_failures_are_retried() {
+    let calls = Arc::new(AtomicU32::new(0));
+    let counter = Arc::clone(&calls);
+    let server = MockServer::start(move |req| match req.path() {
+        "/api/v2/words/en-us" => match counter.fetch_add(1, Ordering::SeqCst) {
+            0 => Response::status(503),
+            1 => Response::status(429).header("Retry-After", "0"),
+            _ => fixture_response("words/rust.json"),
+        },
+        _ => Response::status(404),
+    });
+    let retries = Arc::new(Mutex::new(vec![]));
+    let log = Arc::clone(&retries);
+    let policy = RetryPolicy::new(3)
+        .base_delay(Duration::from_millis(10))
+        .on_retry(move |event| log.lock().unwrap().push(event.attempt));
+    let client = server.builder().retry(policy).build().unwrap();
+
+    assert!(client.entry("rust").is_ok());
+    assert_eq!(calls.load(Ordering::SeqCst), 3);
+    assert!(matches!(
+        client.lemmas("missing"),
+        Err(OxdError::NotFound(_))
+    ));
+    assert_eq!(*retries.lock().unwrap(), [1, 2]);
+}
+
+#[test]
+fn monthly_quota_is_shared_through_the_state_file() {
+    let dir = temp_dir("quota");
+    let path = dir.join("quota.json");
+    let plan = Plan {
+        per_minute: None,
+        per_month: Some(2),
+    };
+    let server = MockServer::fixtures();
+    let limiter = RateLimiter::with_state_file(plan, &path);
+    let client = server.builder().rate_limiter(limiter).build().unwrap();
+    client.entry("rust").unwrap();
+    client.entry("rusty").unwrap();
+
+    let another_run = RateLimiter::with_state_file(plan, &path);
+    assert_eq!(another_run.used_this_month().unwrap(), 2);
+    let client = server.builder().rate_limiter(another_run).build().unwrap();
+    assert!(matches!(
+        client.entry("rust"),
+        Err(OxdError::QuotaExceeded { limit: 2 })
+    ));
+    assert_eq!(server.requests().len(), 2);
+}
+
+#[test]
+fn cached_responses_are_reused_until_refreshed() {
+    let dir = temp_dir("cache");
+    let server = MockServer::fixtures();
+    let verbs = LookupFilters::default().lexical_category("verb");
+
+    let client = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .build()
+        .unwrap();
+    client.entry("rust").unwrap();
+    client.entry("rust").unwrap();
+    client.entry_with("rust", &verbs).unwrap();
+    assert!(dir.join("words/en-us/rust.json").exists());
+    let files = std::fs::read_dir(dir.join("words/en-us")).unwrap().count();
+    assert_eq!(files, 2, "only the two responses, without temporary files");
+    assert_eq!(server.requests().len(), 2);
+
+    let refreshing = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()).refresh(true))
+        .build()
+        .unwrap();
+    refreshing.entry("rust").unwrap();
+    assert_eq!(server.requests().len(), 3);
+
+    let expired = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()).ttl(Some(Duration::ZERO)))
+        .build()
+        .unwrap();
+    std::thread::sleep(Duration::from_millis(1100));
+    expired.entry("rust").unwrap();
+    assert_eq!(server.requests().len(), 4);
+}
+
+#[test]
+fn failing_cache_writes_do_not_fail_lookups() {
+    // A file where the cache directory should be makes every write fail.
+    let dir = temp_dir("unwritable");
+    std::fs::write(dir.path(), "").unwrap();
+    let server = MockServer::fixtures();
+    let client = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .build()
+        .unwrap();
+    assert_eq!(
+        client.entry("rust").unwrap().headword_entries[0].word,
+        "rust"
+    );
+}
+
+#[test]
+fn offline_lookups_follow_cached_roots() {
+    let dir = temp_dir("offline");
+    let server = MockServer::fixtures();
+    let online = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()))
+        .build()
+        .unwrap();
+    online.entries("rusty").unwrap();
+    online.labels(LabelKind::Domains).unwrap();
+    let sent = server.requests().len();
+
+    let offline = server
+        .builder()
+        .cache(ResponseCache::new(dir.path()).ttl(Some(Duration::ZERO)))
+        .offline(true)
+        .build()
+        .unwrap();
+    let lookup = offline.entries("rusty").unwrap();
+    let words: Vec<_> = lookup
+        .in_display_order()
+        .iter()
+        .map(|found| found.entry.headword_entries[0].word.as_str())
+        .collect();
+    assert_eq!(words, ["rust", "rusty"]);
+    assert!(matches!(
+        offline.entries("rusted"),
+        Err(OxdError::NotCached(word)) if word == "rusted"
+    ));
+    assert!(offline
+        .labels(LabelKind::Domains)
+        .unwrap()
+        .contains("computing"));
+    assert!(matches!(
+        offline.labels(LabelKind::Registers),
+        Err(OxdError::NotCached(_))
+    ));
+    assert_eq!(server.requests().len(), sent);
+
+    let uncached = server.builder().offline(true).build();
+    assert!(matches!(uncached, Err(OxdError::InvalidConfig(_))));
+}
+
+#[test]
+fn many_entries_share_roots_and_keep_their_order() {
+    let server = MockServer::fixtures();
+    let words = ["rusty", "rusted", "qwxz", "rust"];
+    let lookups = server.client().get_many(&words, 3);
+
+    assert_eq!(lookups.len(), 4);
+    for (word, lookup) in words.iter().zip(&lookups) {
+        match lookup {
+            Ok(lookup) => assert_eq!(lookup.word, *word),
+            Err(err) => assert!(matches!(err, OxdError::NotFound(w) if w == "qwxz")),
+        }
+    }
+    assert!(lookups[2].is_err());
+    assert_eq!(lookups[0].as_ref().unwrap().entries.len(), 2);
+    assert_eq!(lookups[1].as_ref().unwrap().entries.len(), 1);
+
+    let rust_fetches = server
+        .requests()
+        .iter()
+        .filter(|req| req.target == "/api/v2/words/en-us?q=rust")
+        .count();
+    assert_eq!(rust_fetches, 1);
+}
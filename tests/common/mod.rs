@@ -0,0 +1,251 @@
+//! A tiny HTTP server standing in for the OD API in tests, and fresh temporary paths.
+
+#![allow(dead_code)]
+
+#[cfg(feature = "blocking")]
+use oxd::OxdClient;
+#[cfg(any(feature = "blocking", feature = "async"))]
+use oxd::OxdClientBuilder;
+use oxd::{Language, TranslationLanguage};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A request received by the mock server.
+#[derive(Debug, Clone)]
+pub struct Request {
+    /// Path and query, e.g. `/words/en-us?q=rust`.
+    pub target: String,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Request {
+    pub fn path(&self) -> &str {
+        self.target.split('?').next().unwrap_or_default()
+    }
+
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A response sent by the mock server.
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl Response {
+    pub fn json(body: impl Into<String>) -> Self {
+        Response {
+            status: 200,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    pub fn status(status: u16) -> Self {
+        Response {
+            status,
+            headers: vec![],
+            body: String::new(),
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_owned(), value.to_owned()));
+        self
+    }
+}
+
+pub struct MockServer {
+    pub url: String,
+    requests: Arc<Mutex<Vec<Request>>>,
+}
+
+impl MockServer {
+    /// Serves every request with `handler` on a random local port.
+    pub fn start<F>(handler: F) -> Self
+    where
+        F: Fn(&Request) -> Response + Send + Sync + 'static,
+    {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/api/v2/", listener.local_addr().unwrap());
+        let requests = Arc::new(Mutex::new(vec![]));
+        let handler = Arc::new(handler);
+        let recorded = Arc::clone(&requests);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let handler = Arc::clone(&handler);
+                let recorded = Arc::clone(&recorded);
+                thread::spawn(move || serve(stream, handler.as_ref(), &recorded));
+            }
+        });
+        MockServer { url, requests }
+    }
+
+    /// Serves `tests/fixtures/{endpoint}/{word}.json` for any endpoint,
+    /// taking the word from `?q=word` or the last path segment.
+    pub fn fixtures() -> Self {
+        MockServer::start(|req| {
+            let segments: Vec<_> = req
+                .path()
+                .trim_start_matches("/api/v2/")
+                .split('/')
+                .collect();
+            let (endpoint, language) = match segments[..] {
+                ["search", "thesaurus", language, ..] => ("search/thesaurus", language),
+                [endpoint, language, ..] => (endpoint, language),
+                _ => return Response::status(404),
+            };
+            if !serves(endpoint, language) {
+                return Response::status(400);
+            }
+            let endpoint = segments[0];
+            let word = match req.target.split_once("q=") {
+                Some((_, query)) => query.split('&').next().unwrap_or_default(),
+                None => segments.last().copied().unwrap_or_default(),
+            };
+            fixture_response(&format!("{endpoint}/{word}.json"))
+        })
+    }
+
+    pub fn requests(&self) -> Vec<Request> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    #[cfg(any(feature = "blocking", feature = "async"))]
+    pub fn builder(&self) -> OxdClientBuilder {
+        OxdClientBuilder::default()
+            .app_id("test_id")
+            .app_key("test_key")
+            .base_url(&self.url)
+    }
+
+    #[cfg(feature = "blocking")]
+    pub fn client(&self) -> OxdClient {
+        self.builder().build().unwrap()
+    }
+}
+
+/// Whether OD serves `endpoint` in `language`: the dictionaries tell British from American
+/// English, while lemmas, sentences and the thesaurus share a single `en` dataset.
+fn serves(endpoint: &str, language: &str) -> bool {
+    match endpoint {
+        "words" | "search" => Language::CODES.contains(&language),
+        "lemmas" | "sentences" | "thesaurus" | "search/thesaurus" => {
+            language == "en" || Language::CODES.contains(&language) && !language.starts_with("en-")
+        }
+        "translations" => TranslationLanguage::CODES.contains(&language),
+        _ => true,
+    }
+}
+
+/// Reads a fixture, or answers 404 like OD does for unknown words.
+pub fn fixture_response(name: &str) -> Response {
+    match std::fs::read_to_string(fixture_path(name)) {
+        Ok(body) => Response::json(body),
+        Err(_) => Response::status(404),
+    }
+}
+
+pub fn fixture_path(name: &str) -> String {
+    format!("{}/tests/fixtures/{name}", env!("CARGO_MANIFEST_DIR"))
+}
+
+/// A path under the system temporary directory, unique to the test process,
+/// removed together with whatever is there when dropped.
+pub struct TempDir(PathBuf);
+
+/// A fresh [TempDir] named after `name`; nothing exists there yet.
+pub fn temp_dir(name: &str) -> TempDir {
+    let path = std::env::temp_dir().join(format!("oxd-{name}-{}", std::process::id()));
+    let dir = TempDir(path);
+    dir.remove();
+    dir
+}
+
+impl TempDir {
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    fn remove(&self) {
+        let _ = std::fs::remove_dir_all(&self.0);
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        self.remove();
+    }
+}
+
+fn serve<F: Fn(&Request) -> Response>(
+    stream: TcpStream,
+    handler: &F,
+    recorded: &Mutex<Vec<Request>>,
+) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line).unwrap_or(0) == 0 {
+            return;
+        }
+        let target = request_line
+            .split_whitespace()
+            .nth(1)
+            .unwrap_or_default()
+            .to_owned();
+        let mut headers = vec![];
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_owned(), value.trim().to_owned()));
+            }
+        }
+        let request = Request { target, headers };
+        recorded.lock().unwrap().push(request.clone());
+
+        let response = handler(&request);
+        let mut out = &stream;
+        write!(out, "HTTP/1.1 {} Mock\r\n", response.status).unwrap();
+        for (name, value) in &response.headers {
+            write!(out, "{name}: {value}\r\n").unwrap();
+        }
+        write!(
+            out,
+            "Content-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
+            response.body.len(),
+            response.body
+        )
+        .unwrap();
+    }
+}
@@ -0,0 +1,414 @@
+use oxd::backend::MemoryBackend;
+use oxd::dict::{DictClient, DictServer, Listing, Match};
+use oxd::display::Display;
+use oxd::{DictionaryBackend, OxdError};
+use std::io::{BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// A database of the stub server: name, description and the words it defines.
+type Database = (
+    &'static str,
+    &'static str,
+    &'static [(&'static str, &'static str)],
+);
+
+const DATABASES: [Database; 2] = [
+    (
+        "glossary",
+        "Lab glossary",
+        &[
+            ("rust", "rust\n   A systems programming language.\n\n.rs files hold its source.\n"),
+            ("rustc", "rustc\n   The Rust compiler.\n"),
+        ],
+    ),
+    (
+        "wn",
+        "WordNet (r) 3.0 (2006)",
+        &[(
+            "rust",
+            "rust\n    n 1: a red or brown oxide coating on iron or steel\n         in the presence of moisture\n    2: a plant disease\n",
+        )],
+    ),
+];
+
+/// A dictd stand-in on a local port, recording the commands it receives.
+struct StubServer {
+    port: u16,
+    commands: Arc<Mutex<Vec<String>>>,
+}
+
+impl StubServer {
+    fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let commands = Arc::new(Mutex::new(vec![]));
+        let recorded = commands.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let recorded = recorded.clone();
+                thread::spawn(move || serve(stream, &recorded));
+            }
+        });
+        StubServer { port, commands }
+    }
+
+    fn client(&self) -> DictClient {
+        DictClient::new("127.0.0.1").port(self.port)
+    }
+
+    fn commands(&self) -> Vec<String> {
+        self.commands.lock().unwrap().clone()
+    }
+}
+
+fn serve(stream: TcpStream, recorded: &Mutex<Vec<String>>) {
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let mut reply = |text: String| writer.write_all(text.replace('\n', "\r\n").as_bytes());
+    reply("220 stub dictd <mime> <1@stub>\n".to_owned()).unwrap();
+    let mut line = String::new();
+    while reader.read_line(&mut line).unwrap_or(0) > 0 {
+        let command = line.trim_end().to_owned();
+        line.clear();
+        recorded.lock().unwrap().push(command.clone());
+        let words = split(&command);
+        let words: Vec<&str> = words.iter().map(String::as_str).collect();
+        let text = match words[..] {
+            ["CLIENT", ..] => "250 ok\n".to_owned(),
+            ["SHOW", "DB"] => {
+                let mut text = format!("110 {} databases present\n", DATABASES.len());
+                for (name, description, _) in DATABASES {
+                    text.push_str(&format!("{name} \"{description}\"\n"));
+                }
+                text + ".\n250 ok\n"
+            }
+            ["SHOW", "STRAT"] => {
+                "111 2 strategies present\nexact \"Match headwords exactly\"\nprefix \"Match prefixes\"\n.\n250 ok\n".to_owned()
+            }
+            ["DEFINE", database, word] => define(database, word),
+            ["MATCH", database, strategy, word] => find(database, strategy, word),
+            ["QUIT"] => {
+                let _ = reply("221 bye\n".to_owned());
+                return;
+            }
+            _ => "500 unknown command\n".to_owned(),
+        };
+        reply(text).unwrap();
+    }
+}
+
+fn databases(database: &str) -> Option<Vec<Database>> {
+    match database {
+        "*" | "!" => Some(DATABASES.to_vec()),
+        _ => {
+            let found: Vec<_> = DATABASES
+                .into_iter()
+                .filter(|(name, ..)| *name == database)
+                .collect();
+            (!found.is_empty()).then_some(found)
+        }
+    }
+}
+
+fn define(database: &str, word: &str) -> String {
+    let Some(databases) = databases(database) else {
+        return "550 invalid database\n".to_owned();
+    };
+    let mut definitions = vec![];
+    for (name, description, words) in databases {
+        for (headword, text) in words {
+            if *headword == word {
+                let text: String = text
+                    .lines()
+                    .map(|line| match line.starts_with('.') {
+                        true => format!(".{line}\n"),
+                        false => format!("{line}\n"),
+                    })
+                    .collect();
+                definitions.push(format!(
+                    "151 \"{headword}\" {name} \"{description}\"\n{text}.\n"
+                ));
+            }
+        }
+        if database == "!" && !definitions.is_empty() {
+            break;
+        }
+    }
+    if definitions.is_empty() {
+        return "552 no match\n".to_owned();
+    }
+    format!(
+        "150 {} definitions retrieved\n{}250 ok\n",
+        definitions.len(),
+        definitions.concat()
+    )
+}
+
+fn find(database: &str, strategy: &str, word: &str) -> String {
+    let Some(databases) = databases(database) else {
+        return "550 invalid database\n".to_owned();
+    };
+    let matches = |headword: &str| match strategy {
+        "exact" => headword == word,
+        "prefix" | "." => headword.starts_with(word),
+        _ => false,
+    };
+    if !matches!(strategy, "exact" | "prefix" | ".") {
+        return "551 invalid strategy\n".to_owned();
+    }
+    let found: Vec<String> = databases
+        .iter()
+        .flat_map(|(name, _, words)| {
+            words
+                .iter()
+                .filter(|(headword, _)| matches(headword))
+                .map(move |(headword, _)| format!("{name} \"{headword}\"\n"))
+        })
+        .collect();
+    if found.is_empty() {
+        return "552 no match\n".to_owned();
+    }
+    format!(
+        "152 {} matches found\n{}.\n250 ok\n",
+        found.len(),
+        found.concat()
+    )
+}
+
+/// Splits a command into words, keeping quoted words whole.
+fn split(command: &str) -> Vec<String> {
+    let mut words = vec![];
+    let mut rest = command.trim();
+    while !rest.is_empty() {
+        if let Some(quoted) = rest.strip_prefix('"') {
+            let end = quoted.find('"').unwrap_or(quoted.len());
+            words.push(quoted[..end].to_owned());
+            rest = quoted.get(end + 1..).unwrap_or_default().trim_start();
+        } else {
+            let end = rest.find(' ').unwrap_or(rest.len());
+            words.push(rest[..end].to_owned());
+            rest = rest[end..].trim_start();
+        }
+    }
+    words
+}
+
+#[test]
+fn databases_and_strategies_are_listed() {
+    let server = StubServer::start();
+    let client = server.client();
+    let databases = client.databases().unwrap();
+    assert_eq!(
+        databases[1],
+        Listing {
+            name: "wn".to_owned(),
+            description: "WordNet (r) 3.0 (2006)".to_owned()
+        }
+    );
+    let strategies: Vec<_> = client
+        .strategies()
+        .unwrap()
+        .into_iter()
+        .map(|strategy| strategy.name)
+        .collect();
+    assert_eq!(strategies, ["exact", "prefix"]);
+    let commands = server.commands();
+    assert!(commands[0].starts_with("CLIENT oxd "));
+    assert!(commands.contains(&"QUIT".to_owned()));
+}
+
+#[test]
+fn definitions_become_entries() {
+    let server = StubServer::start();
+    let client = server.client();
+    let definitions = client.define("rust").unwrap();
+    assert_eq!(definitions.len(), 2);
+    assert!(definitions[0].text.contains("\n.rs files hold its source."));
+
+    let entry = client.entry("rust").unwrap();
+    let lexical_entries = &entry.headword_entries[0].lexical_entries;
+    let databases: Vec<_> = lexical_entries
+        .iter()
+        .map(|lexical_entry| lexical_entry.lexical_category.id.as_str())
+        .collect();
+    assert_eq!(databases, ["glossary", "wn"]);
+    let senses = lexical_entries[1].entries[0].senses.as_ref().unwrap();
+    assert_eq!(senses.len(), 2);
+
+    let mut canvas = String::new();
+    client
+        .clone()
+        .database("wn")
+        .lookup("rust")
+        .unwrap()
+        .into_display_order()[0]
+        .display(&mut canvas);
+    assert!(canvas
+        .contains("a red or brown oxide coating on iron or steel in the presence of moisture"));
+    assert!(!canvas.contains("systems programming"));
+
+    assert!(matches!(client.entry("iron"), Err(OxdError::NotFound(_))));
+    assert!(matches!(
+        client.clone().database("jargon").entry("rust"),
+        Err(OxdError::InvalidConfig(_))
+    ));
+    assert!(server.commands().contains(&"DEFINE wn rust".to_owned()));
+}
+
+#[test]
+fn matches_are_suggestions() {
+    let server = StubServer::start();
+    let client = server.client();
+    assert_eq!(
+        client.matches("rus", "prefix").unwrap()[1],
+        Match {
+            database: "glossary".to_owned(),
+            word: "rustc".to_owned()
+        }
+    );
+    let suggestions = client.clone().strategy("prefix").search("rus", 5).unwrap();
+    let words: Vec<_> = suggestions
+        .ranked()
+        .iter()
+        .map(|result| result.word.as_str())
+        .collect();
+    assert_eq!(words, ["rust", "rustc"]);
+    assert!(client.matches("iron", "exact").unwrap().is_empty());
+    assert!(matches!(
+        client.matches("rus", "soundex"),
+        Err(OxdError::InvalidConfig(_))
+    ));
+    assert!(client.define("ice cream").is_err());
+    assert!(server
+        .commands()
+        .contains(&"DEFINE * \"ice cream\"".to_owned()));
+}
+
+#[test]
+fn unreachable_servers_are_named_by_url() {
+    let port = TcpListener::bind("127.0.0.1:0")
+        .unwrap()
+        .local_addr()
+        .unwrap()
+        .port();
+    let url = format!("dict://127.0.0.1:{port}");
+    let err = DictClient::from_url(&url)
+        .unwrap()
+        .define("rust")
+        .unwrap_err();
+    assert!(matches!(&err, OxdError::Dict { url: named, .. } if *named == url));
+    assert!(err
+        .to_string()
+        .starts_with(&format!("DICT server {url} failed")));
+}
+
+/// Serves the saved OD responses of the fixtures on a local port.
+fn start_server() -> DictClient {
+    DictClient::new("127.0.0.1").port(start_server_for(32))
+}
+
+/// Like [start_server], to at most `max_connections` clients at once, returning the port.
+fn start_server_for(max_connections: usize) -> u16 {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port();
+    thread::spawn(move || {
+        let fixtures = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures");
+        let backend = MemoryBackend::load_dir(fixtures).unwrap();
+        DictServer::new(&backend, "Saved responses")
+            .max_connections(max_connections)
+            .serve(listener);
+    });
+    port
+}
+
+#[test]
+fn server_defines_lookups() {
+    let client = start_server();
+    assert_eq!(
+        client.databases().unwrap(),
+        [Listing {
+            name: "oxd".to_owned(),
+            description: "Saved responses".to_owned()
+        }]
+    );
+    assert_eq!(client.strategies().unwrap().len(), 3);
+
+    let definitions = client.define("rusted").unwrap();
+    assert_eq!(definitions.len(), 1);
+    assert_eq!(definitions[0].word, "rust");
+    assert_eq!(definitions[0].database, "oxd");
+    assert!(definitions[0]
+        .text
+        .starts_with("rusted → rust (past)\nrust"));
+    assert!(!definitions[0].text.contains('\u{1b}'));
+    let words: Vec<_> = client
+        .define("rusty")
+        .unwrap()
+        .into_iter()
+        .map(|definition| definition.word)
+        .collect();
+    assert_eq!(words, ["rust", "rusty"]);
+
+    assert!(matches!(client.define("iron"), Err(OxdError::NotFound(_))));
+    assert!(matches!(
+        client.clone().database("wn").define("rust"),
+        Err(OxdError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn server_matches_words() {
+    let client = start_server();
+    let words = |strategy: &str, query: &str| -> Vec<String> {
+        client
+            .matches(query, strategy)
+            .unwrap()
+            .into_iter()
+            .map(|found| found.word)
+            .collect()
+    };
+    assert_eq!(words("exact", "rust"), ["rust"]);
+    assert_eq!(words("prefix", "rust"), ["rust", "rusty"]);
+    assert_eq!(words("lev", "rsut"), ["rust", "rusty"]);
+    assert!(words("exact", "iron").is_empty());
+    assert!(matches!(
+        client.matches("rust", "soundex"),
+        Err(OxdError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn server_turns_away_extra_clients_and_long_lines() {
+    let port = start_server_for(1);
+    let stream = TcpStream::connect(("127.0.0.1", port)).unwrap();
+    let mut reader = BufReader::new(stream.try_clone().unwrap());
+    let mut writer = stream;
+    let mut line = String::new();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.starts_with("220 "));
+
+    let client = DictClient::new("127.0.0.1").port(port);
+    assert!(client.databases().is_err());
+
+    writer
+        .write_all(format!("DEFINE * {}\r\nSTATUS\r\n", "a".repeat(5000)).as_bytes())
+        .unwrap();
+    for expected in ["500 ", "210 "] {
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert!(line.starts_with(expected), "{line}");
+    }
+    writer.write_all(b"QUIT\r\n").unwrap();
+    line.clear();
+    reader.read_line(&mut line).unwrap();
+    assert!(line.starts_with("221 "));
+
+    // The server may take a moment to notice the first client has left.
+    let listed = (0..50).any(|_| {
+        thread::sleep(std::time::Duration::from_millis(20));
+        client.databases().is_ok()
+    });
+    assert!(listed);
+}
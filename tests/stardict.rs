@@ -0,0 +1,83 @@
+use oxd::display::Display;
+use oxd::stardict::StarDict;
+use oxd::{DictionaryBackend, OxdError};
+
+fn open(name: &str) -> StarDict {
+    StarDict::open(format!(
+        "{}/tests/fixtures/stardict/{name}",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap()
+}
+
+#[test]
+fn articles_are_read_from_dictzip_chunks() {
+    let metals = open("metals.ifo");
+    assert_eq!(metals.name(), "Metals (German-English)");
+    assert_eq!(metals.len(), 5);
+
+    let entry = metals.entry("rost").unwrap();
+    let headword = &entry.headword_entries[0];
+    assert_eq!(headword.word, "Rost");
+    assert_eq!(headword.language, "de-en");
+    assert_eq!(headword.lexical_entries.len(), 2);
+
+    let mut canvas = String::new();
+    entry.display(&mut canvas);
+    assert!(canvas.contains("/ʁɔst/"));
+    assert!(canvas.contains("corrosion of iron"));
+    assert!(canvas.contains("grill"));
+
+    let entry = metals.entry("Zinn").unwrap();
+    let mut canvas = String::new();
+    entry.display(&mut canvas);
+    assert!(canvas.contains("tin"));
+
+    assert_eq!(shown(&metals, "rosten"), ["rosten"]);
+    assert!(matches!(
+        metals.lookup("Kupfer"),
+        Err(OxdError::NotFound(_))
+    ));
+    assert_eq!(metals.search("Rots", 3).unwrap().ranked()[0].word, "Rost");
+}
+
+#[test]
+fn markup_is_reduced_to_text() {
+    let glossary = open("glossary");
+    let entry = glossary.entry("iron").unwrap();
+    let senses = entry.headword_entries[0].lexical_entries[0].entries[0]
+        .senses
+        .as_ref()
+        .unwrap();
+    let definitions: Vec<_> = senses
+        .iter()
+        .map(|sense| sense.definitions.as_ref().unwrap()[0].as_str())
+        .collect();
+    assert_eq!(
+        definitions,
+        ["iron", "a strong, hard magnetic metal & element"]
+    );
+
+    let mut html = String::new();
+    glossary.entry("rust").unwrap().to_html(&mut html);
+    assert!(html.contains("reddish-brown flaking coating on iron"));
+}
+
+#[test]
+fn a_directory_must_hold_one_dictionary() {
+    let dir = format!("{}/tests/fixtures/stardict", env!("CARGO_MANIFEST_DIR"));
+    assert!(matches!(
+        StarDict::open(dir),
+        Err(OxdError::InvalidConfig(_))
+    ));
+}
+
+fn shown(dictionary: &StarDict, word: &str) -> Vec<String> {
+    dictionary
+        .lookup(word)
+        .unwrap()
+        .into_display_order()
+        .into_iter()
+        .map(|entry| entry.headword_entries[0].word.clone())
+        .collect()
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use common::{fixture_path, temp_dir, TempDir};
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use oxd::display::Display;
+use oxd::wiktionary::{self, Wiktionary};
+use oxd::{DictionaryBackend, OxdError};
+use std::fs::File;
+use std::io::{BufReader, Write};
+use std::path::Path;
+
+/// Imports the fixture extract into a fresh store.
+fn import(name: &str) -> TempDir {
+    let dir = temp_dir(&format!("wiktionary-{name}"));
+    import_into(&dir);
+    dir
+}
+
+fn import_into(dir: &Path) {
+    let extract = fixture_path("wiktionary/extract.jsonl");
+    let summary = wiktionary::import(BufReader::new(File::open(extract).unwrap()), dir).unwrap();
+    assert_eq!(summary.records["en"], 5);
+    assert_eq!(summary.records["de"], 1);
+    assert_eq!(summary.skipped, 1);
+}
+
+#[test]
+fn records_map_onto_entries() {
+    let dir = import("entries");
+    let english = Wiktionary::open(&dir, "en").unwrap();
+    let entry = english.entry("rust").unwrap();
+    let headwords: Vec<_> = entry
+        .headword_entries
+        .iter()
+        .map(|headword| headword.word.as_str())
+        .collect();
+    assert_eq!(headwords, ["rust", "Rust"]);
+
+    let noun = &entry.headword_entries[0].lexical_entries[0];
+    assert_eq!(noun.lexical_category.id, "noun");
+    let senses = noun.entries[0].senses.as_ref().unwrap();
+    assert_eq!(senses.len(), 2);
+    assert_eq!(senses[0].subsenses.as_ref().unwrap().len(), 1);
+    assert_eq!(senses[0].domains.as_ref().unwrap()[0].id, "chemistry");
+    let pronunciations = noun.entries[0].pronunciations.as_ref().unwrap();
+    assert_eq!(pronunciations[0].phonetic_spelling.as_deref(), Some("ɹʌst"));
+    assert!(pronunciations[1]
+        .audio_file
+        .as_ref()
+        .unwrap()
+        .ends_with(".mp3"));
+
+    let mut canvas = String::new();
+    entry.display(&mut canvas);
+    assert!(canvas.contains("/ɹʌst/"));
+    assert!(canvas.contains("From Middle English rust"));
+    assert!(canvas.contains("A similar substance based on another metal"));
+    assert!(canvas.contains("To grow stale from disuse"));
+    assert!(canvas.contains("A programming language"));
+
+    let german = Wiktionary::open(&dir, "de").unwrap();
+    let mut canvas = String::new();
+    german.entry("rost").unwrap().display(&mut canvas);
+    assert!(canvas.contains("Das Auto hat Rost."));
+    assert!(matches!(
+        Wiktionary::open(&dir, "fr"),
+        Err(OxdError::InvalidConfig(_))
+    ));
+}
+
+#[test]
+fn inflected_forms_lead_to_their_headwords() {
+    let dir = import("forms");
+    let english = Wiktionary::open(&dir, "en").unwrap();
+
+    let lookup = english.lookup("rusted").unwrap();
+    assert_eq!(lookup.headwords, ["rusted", "rust"]);
+    let mut canvas = String::new();
+    lookup.lemmas.display(&mut canvas);
+    assert!(canvas.contains("rusted → rust (participle past)"));
+
+    let lookup = english.lookup("yote").unwrap();
+    assert_eq!(lookup.headwords, ["yeet"]);
+    let mut canvas = String::new();
+    lookup.into_display_order()[0].display(&mut canvas);
+    assert!(canvas.contains("To throw with force."));
+
+    assert!(matches!(english.lookup("qwxz"), Err(OxdError::NotFound(_))));
+    assert_eq!(english.search("yeat", 3).unwrap().ranked()[0].word, "yeet");
+
+    // Importing again replaces the store instead of adding to it.
+    import_into(&dir);
+    assert_eq!(
+        Wiktionary::open(&dir, "en")
+            .unwrap()
+            .entry("yeet")
+            .unwrap()
+            .headword_entries
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn failed_imports_leave_the_store_alone() {
+    let dir = import("failed");
+    let extract = std::fs::read(fixture_path("wiktionary/extract.jsonl")).unwrap();
+    let mut encoder = GzEncoder::new(vec![], Compression::default());
+    encoder.write_all(&extract).unwrap();
+    let mut gzipped = encoder.finish().unwrap();
+    gzipped.truncate(gzipped.len() - 8);
+    let broken = temp_dir("wiktionary-broken.jsonl.gz");
+    std::fs::write(&broken, gzipped).unwrap();
+
+    assert!(matches!(
+        wiktionary::import_file(&broken, &dir),
+        Err(OxdError::Io { path, .. }) if path == broken.path()
+    ));
+    let english = Wiktionary::open(&dir, "en").unwrap();
+    assert!(english.entry("yeet").is_ok());
+    let mut stores: Vec<_> = std::fs::read_dir(&dir)
+        .unwrap()
+        .map(|store| store.unwrap().file_name())
+        .collect();
+    stores.sort();
+    assert_eq!(stores, ["de", "en"]);
+}
@@ -0,0 +1,61 @@
+use oxd::display::Display;
+use oxd::wordnet::WordNet;
+use oxd::{DictionaryBackend, OxdError};
+
+fn wordnet() -> WordNet {
+    WordNet::open(format!(
+        "{}/tests/fixtures/wordnet",
+        env!("CARGO_MANIFEST_DIR")
+    ))
+    .unwrap()
+}
+
+#[test]
+fn synsets_become_senses_per_part_of_speech() {
+    let entry = wordnet().entry("rust").unwrap();
+    let headword = &entry.headword_entries[0];
+    let categories: Vec<_> = headword
+        .lexical_entries
+        .iter()
+        .map(|lexical_entry| lexical_entry.lexical_category.id.as_str())
+        .collect();
+    assert_eq!(categories, ["noun", "verb"]);
+
+    let mut canvas = String::new();
+    entry.display(&mut canvas);
+    assert!(canvas.contains("any of various fungi causing rust disease in plants"));
+    assert!(canvas.contains("the pipes were covered with rust"));
+    assert!(canvas.contains("also rust fungus"));
+    assert!(canvas.contains("become coated with oxide"));
+
+    let mut html = String::new();
+    entry.to_html(&mut html);
+    assert!(html.contains("the pipes rusted"));
+}
+
+#[test]
+fn inflected_forms_are_looked_up_by_their_base_form() {
+    let wordnet = wordnet();
+    let lookup = wordnet.lookup("rusted").unwrap();
+    assert_eq!(lookup.headwords, ["rust"]);
+    let mut canvas = String::new();
+    lookup.lemmas.display(&mut canvas);
+    assert!(canvas.contains("rusted → rust"));
+
+    let lookup = wordnet.lookup("mice").unwrap();
+    assert_eq!(lookup.headwords, ["mouse"]);
+    let lookup = wordnet.lookup("iron oxide").unwrap();
+    assert_eq!(lookup.entries[0].entry.word.as_deref(), Some("iron oxide"));
+    assert!(matches!(wordnet.lookup("qwxz"), Err(OxdError::NotFound(_))));
+
+    assert_eq!(wordnet.search("rsut", 3).unwrap().ranked()[0].word, "rust");
+}
+
+#[test]
+fn a_directory_without_wordnet_is_rejected() {
+    let dir = format!("{}/tests/fixtures/words", env!("CARGO_MANIFEST_DIR"));
+    assert!(matches!(
+        WordNet::open(dir),
+        Err(OxdError::InvalidConfig(_))
+    ));
+}